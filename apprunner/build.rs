@@ -0,0 +1,5 @@
+fn main() {
+    // Exports the executable's symbols so dladdr can name them in crash
+    // backtraces
+    println!("cargo:rustc-link-arg-bins=-rdynamic");
+}
@@ -1,41 +1,185 @@
 #![no_std]
 #![no_main]
 
-use core::panic::PanicInfo;
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    panic::PanicInfo
+};
 use c::types::{c_int, c_char};
-use lstd::{abort, println};
+use lstd::{
+    abort,
+    alloc::Malloc,
+    backtrace,
+    crash,
+    env::{self, FlagError},
+    error::Error,
+    fs::Path,
+    log,
+    time::{Duration, Instant}
+};
 use tortuga::{
-    window::{Window, create_window},
-    render::Context
+    app::{Application, Runner},
+    render::{Context, Device},
+    time::FrameTimer
 };
 
-#[link(name = "asan")]
-extern {}
+/// How often frame statistics are logged
+const STATS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Crash reports are appended here as well as written to stderr
+const CRASH_LOG: &str = "crash.log";
+
+lstd::flags! {
+    struct Options {
+        /// Window width in pixels
+        width: u16 = 640,
+        /// Window height in pixels
+        height: u16 = 480,
+        /// Index of the GPU to render with, as listed at startup
+        gpu: usize = 0,
+        /// Enable the Vulkan validation layers
+        validation: bool = false,
+        /// Frame rate cap, or 0 for none
+        max_fps: u32 = 0
+    }
+}
+
+struct App {
+    gpu: usize,
+    device: Option<Device>,
+    last_stats: Instant
+}
+
+#[derive(Debug)]
+enum StartupError {
+    Tortuga(tortuga::Error),
+    NoSuchGpu {
+        index: usize,
+        count: usize
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Malloc = Malloc;
 
 #[panic_handler]
 fn panic_handler(info: &PanicInfo) -> ! {
-    #[cfg(debug_assertions)]
-    if let Some(location) = info.location() {
-        println!("Panic in {}:{}", location.file(), location.line())
-    }
+    crash::report(info);
+    log::try_flush();
 
     abort()
 }
 
 #[no_mangle]
-extern fn main(_: c_int, _: *const *const c_char) -> c_int {
-    let window = create_window("Test", 640, 480).unwrap();
-    let context = Context::new(&window).unwrap();
-    let physical_devices = context.get_physical_devices().unwrap();
-    let device = context.create_device(&physical_devices[0]).unwrap();
-
-    'main: loop {
-        if window.should_close() {
-            break 'main;
+extern fn main(argc: c_int, argv: *const *const c_char) -> c_int {
+    unsafe { env::init_args(argc, argv); }
+    backtrace::set_resolver(dl::symbolize);
+    if let Err(error) = crash::install(Some(Path::new(CRASH_LOG))) {
+        lstd::warn!("could not install crash handlers: {}", error);
+    }
+
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(FlagError::Help) => {
+            lstd::print!("Usage: apprunner [flags]\n\n{}", Options::usage());
+            return 0;
         }
+        Err(error) => {
+            lstd::eprint!("{}\n\nUsage: apprunner [flags]\n\n{}", error, Options::usage());
+            return 2;
+        }
+    };
+
+    let mut app = App {
+        gpu: options.gpu,
+        device: None,
+        last_stats: Instant::now()
+    };
+    let result = Runner::new("Test")
+        .size(options.width, options.height)
+        .validation(options.validation)
+        .frame_limit(options.max_fps)
+        .run(&mut app);
 
-        window.update();
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            report(&error);
+            1
+        }
     }
+}
+
+impl Application for App {
+    type Error = StartupError;
 
-    0
+    fn init(&mut self, context: &Context) -> Result<(), StartupError> {
+        let physical_devices = context.get_physical_devices()?;
+        for (i, device) in physical_devices.iter().enumerate() {
+            lstd::info!("GPU {}: {} ({:?})", i, device.name(), device.kind());
+        }
+        let physical_device = physical_devices.get(self.gpu).ok_or(StartupError::NoSuchGpu {
+            index: self.gpu,
+            count: physical_devices.len()
+        })?;
+        self.device = Some(context.create_device(physical_device)?);
+
+        Ok(())
+    }
+
+    fn update(&mut self, timer: &FrameTimer) {
+        if self.last_stats.elapsed() >= STATS_INTERVAL {
+            lstd::debug!(
+                "{:.1} fps, frame time p50 {:?}, p99 {:?}",
+                timer.fps(),
+                timer.percentile(50.0),
+                timer.percentile(99.0)
+            );
+            self.last_stats = Instant::now();
+        }
+    }
+
+    fn on_resize(&mut self, width: u16, height: u16) {
+        lstd::debug!("resized to {}x{}", width, height);
+    }
+
+    fn shutdown(&mut self) {
+        self.device = None;
+    }
+}
+
+/// Prints the error along with everything that led to it
+fn report(error: &dyn Error) {
+    lstd::error!("startup failed: {}", error);
+
+    let mut source = error.source();
+    while let Some(error) = source {
+        lstd::error!("  caused by: {}", error);
+        source = error.source();
+    }
+}
+
+impl From<tortuga::Error> for StartupError {
+    fn from(error: tortuga::Error) -> StartupError {
+        StartupError::Tortuga(error)
+    }
+}
+
+impl Display for StartupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            StartupError::Tortuga(error) => error.fmt(f),
+            StartupError::NoSuchGpu { index, count } =>
+                write!(f, "there is no GPU {}, only {} found", index, count)
+        }
+    }
+}
+
+impl Error for StartupError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StartupError::Tortuga(error) => error.source(),
+            StartupError::NoSuchGpu { .. } => None
+        }
+    }
 }
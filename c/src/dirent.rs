@@ -0,0 +1,27 @@
+#![allow(non_camel_case_types)]
+
+use crate::types::*;
+
+pub const DT_UNKNOWN: c_uchar = 0;
+pub const DT_DIR: c_uchar = 4;
+pub const DT_REG: c_uchar = 8;
+pub const DT_LNK: c_uchar = 10;
+
+/// Opaque directory stream
+pub enum DIR {}
+
+#[repr(C)]
+pub struct dirent {
+    pub d_ino: c_ulong,
+    pub d_off: c_long,
+    pub d_reclen: c_ushort,
+    pub d_type: c_uchar,
+    pub d_name: [c_char; 256]
+}
+
+#[link(name="c")]
+extern "C" {
+    pub fn opendir(name: *const c_char) -> *mut DIR;
+    pub fn readdir(dirp: *mut DIR) -> *mut dirent;
+    pub fn closedir(dirp: *mut DIR) -> c_int;
+}
@@ -0,0 +1,24 @@
+use crate::types::*;
+
+pub const EPERM: c_int = 1;
+pub const ENOENT: c_int = 2;
+pub const EINTR: c_int = 4;
+pub const EAGAIN: c_int = 11;
+pub const EACCES: c_int = 13;
+pub const EBUSY: c_int = 16;
+pub const EEXIST: c_int = 17;
+pub const ENOTDIR: c_int = 20;
+pub const EISDIR: c_int = 21;
+pub const EINVAL: c_int = 22;
+pub const ENOSPC: c_int = 28;
+pub const EDEADLK: c_int = 35;
+pub const ETIMEDOUT: c_int = 110;
+
+#[link(name="c")]
+extern "C" {
+    pub fn __errno_location() -> *mut c_int;
+}
+
+pub fn errno() -> c_int {
+    unsafe { *__errno_location() }
+}
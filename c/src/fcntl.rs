@@ -0,0 +1,15 @@
+use crate::types::*;
+
+pub const O_RDONLY: c_int = 0o0;
+pub const O_WRONLY: c_int = 0o1;
+pub const O_RDWR: c_int = 0o2;
+pub const O_CREAT: c_int = 0o100;
+pub const O_EXCL: c_int = 0o200;
+pub const O_TRUNC: c_int = 0o1000;
+pub const O_APPEND: c_int = 0o2000;
+pub const O_CLOEXEC: c_int = 0o2000000;
+
+#[link(name="c")]
+extern "C" {
+    pub fn open(pathname: *const c_char, flags: c_int, ...) -> c_int;
+}
@@ -6,3 +6,23 @@ pub mod stdlib;
 
 #[cfg(target_os = "linux")]
 pub mod unistd;
+#[cfg(target_os = "linux")]
+pub mod mman;
+#[cfg(target_os = "linux")]
+pub mod pthread;
+#[cfg(target_os = "linux")]
+pub mod time;
+#[cfg(target_os = "linux")]
+pub mod errno;
+#[cfg(target_os = "linux")]
+pub mod dirent;
+#[cfg(target_os = "linux")]
+pub mod fcntl;
+#[cfg(target_os = "linux")]
+pub mod stat;
+#[cfg(target_os = "linux")]
+pub mod string;
+#[cfg(target_os = "linux")]
+pub mod signal;
+#[cfg(target_os = "linux")]
+pub mod unwind;
@@ -0,0 +1,36 @@
+use crate::types::*;
+
+pub const PROT_NONE: c_int = 0x0;
+pub const PROT_READ: c_int = 0x1;
+pub const PROT_WRITE: c_int = 0x2;
+
+pub const MAP_SHARED: c_int = 0x01;
+pub const MAP_PRIVATE: c_int = 0x02;
+pub const MAP_ANONYMOUS: c_int = 0x20;
+
+pub const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+pub const MADV_NORMAL: c_int = 0;
+pub const MADV_RANDOM: c_int = 1;
+pub const MADV_SEQUENTIAL: c_int = 2;
+pub const MADV_WILLNEED: c_int = 3;
+pub const MADV_DONTNEED: c_int = 4;
+
+pub const MS_ASYNC: c_int = 1;
+pub const MS_SYNC: c_int = 4;
+
+#[link(name="c")]
+extern "C" {
+    pub fn mmap(
+        addr: *mut c_void,
+        length: usize,
+        prot: c_int,
+        flags: c_int,
+        fd: c_int,
+        offset: c_long
+    ) -> *mut c_void;
+    pub fn munmap(addr: *mut c_void, length: usize) -> c_int;
+    pub fn mprotect(addr: *mut c_void, len: usize, prot: c_int) -> c_int;
+    pub fn madvise(addr: *mut c_void, length: usize, advice: c_int) -> c_int;
+    pub fn msync(addr: *mut c_void, length: usize, flags: c_int) -> c_int;
+}
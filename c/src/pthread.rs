@@ -0,0 +1,102 @@
+#![allow(non_camel_case_types)]
+
+use crate::{time::timespec, types::*};
+
+pub type pthread_t = c_ulong;
+pub type pthread_key_t = c_uint;
+
+// Sizes are those of glibc on 64-bit targets. The types are only ever
+// handled through pointers, so their contents stay opaque
+
+#[repr(C)]
+pub struct pthread_attr_t {
+    __size: [u64; 7]
+}
+
+#[repr(C)]
+pub struct pthread_mutex_t {
+    __size: [u64; 5]
+}
+
+#[repr(C)]
+pub struct pthread_cond_t {
+    __size: [u64; 6]
+}
+
+#[repr(C)]
+pub struct pthread_rwlock_t {
+    __size: [u64; 7]
+}
+
+pub const PTHREAD_MUTEX_INITIALIZER: pthread_mutex_t = pthread_mutex_t {
+    __size: [0; 5]
+};
+pub const PTHREAD_COND_INITIALIZER: pthread_cond_t = pthread_cond_t {
+    __size: [0; 6]
+};
+pub const PTHREAD_RWLOCK_INITIALIZER: pthread_rwlock_t = pthread_rwlock_t {
+    __size: [0; 7]
+};
+
+pub const PTHREAD_STACK_MIN: usize = 16384;
+
+#[link(name="pthread")]
+extern "C" {
+    pub fn pthread_create(
+        thread: *mut pthread_t,
+        attr: *const pthread_attr_t,
+        start_routine: extern "C" fn(*mut c_void) -> *mut c_void,
+        arg: *mut c_void
+    ) -> c_int;
+    pub fn pthread_join(thread: pthread_t, retval: *mut *mut c_void) -> c_int;
+    pub fn pthread_detach(thread: pthread_t) -> c_int;
+    pub fn pthread_self() -> pthread_t;
+    pub fn pthread_setname_np(thread: pthread_t, name: *const c_char) -> c_int;
+    pub fn pthread_getname_np(
+        thread: pthread_t,
+        name: *mut c_char,
+        len: usize
+    ) -> c_int;
+
+    pub fn pthread_attr_init(attr: *mut pthread_attr_t) -> c_int;
+    pub fn pthread_attr_destroy(attr: *mut pthread_attr_t) -> c_int;
+    pub fn pthread_attr_setstacksize(
+        attr: *mut pthread_attr_t,
+        stacksize: usize
+    ) -> c_int;
+
+    pub fn pthread_mutex_lock(mutex: *mut pthread_mutex_t) -> c_int;
+    pub fn pthread_mutex_trylock(mutex: *mut pthread_mutex_t) -> c_int;
+    pub fn pthread_mutex_unlock(mutex: *mut pthread_mutex_t) -> c_int;
+    pub fn pthread_mutex_destroy(mutex: *mut pthread_mutex_t) -> c_int;
+
+    pub fn pthread_cond_wait(
+        cond: *mut pthread_cond_t,
+        mutex: *mut pthread_mutex_t
+    ) -> c_int;
+    pub fn pthread_cond_timedwait(
+        cond: *mut pthread_cond_t,
+        mutex: *mut pthread_mutex_t,
+        abstime: *const timespec
+    ) -> c_int;
+    pub fn pthread_cond_signal(cond: *mut pthread_cond_t) -> c_int;
+    pub fn pthread_cond_broadcast(cond: *mut pthread_cond_t) -> c_int;
+    pub fn pthread_cond_destroy(cond: *mut pthread_cond_t) -> c_int;
+
+    pub fn pthread_rwlock_rdlock(rwlock: *mut pthread_rwlock_t) -> c_int;
+    pub fn pthread_rwlock_tryrdlock(rwlock: *mut pthread_rwlock_t) -> c_int;
+    pub fn pthread_rwlock_wrlock(rwlock: *mut pthread_rwlock_t) -> c_int;
+    pub fn pthread_rwlock_trywrlock(rwlock: *mut pthread_rwlock_t) -> c_int;
+    pub fn pthread_rwlock_unlock(rwlock: *mut pthread_rwlock_t) -> c_int;
+    pub fn pthread_rwlock_destroy(rwlock: *mut pthread_rwlock_t) -> c_int;
+
+    pub fn pthread_key_create(
+        key: *mut pthread_key_t,
+        destructor: Option<unsafe extern "C" fn(*mut c_void)>
+    ) -> c_int;
+    pub fn pthread_key_delete(key: pthread_key_t) -> c_int;
+    pub fn pthread_getspecific(key: pthread_key_t) -> *mut c_void;
+    pub fn pthread_setspecific(key: pthread_key_t, value: *const c_void) -> c_int;
+
+    pub fn sched_yield() -> c_int;
+}
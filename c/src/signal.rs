@@ -0,0 +1,63 @@
+#![allow(non_camel_case_types)]
+
+use crate::types::*;
+
+pub const SIGILL: c_int = 4;
+pub const SIGABRT: c_int = 6;
+pub const SIGBUS: c_int = 7;
+pub const SIGFPE: c_int = 8;
+pub const SIGSEGV: c_int = 11;
+
+pub const SA_SIGINFO: c_int = 0x4;
+pub const SA_ONSTACK: c_int = 0x0800_0000;
+pub const SA_RESETHAND: c_int = 0x8000_0000u32 as c_int;
+
+pub const SIG_DFL: usize = 0;
+
+pub const MINSIGSTKSZ: usize = 2048;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct sigset_t {
+    pub val: [c_ulong; 16]
+}
+
+/// Only the fields shared by every signal, plus the faulting address that
+/// SIGSEGV, SIGBUS, SIGILL and SIGFPE fill in
+#[repr(C)]
+pub struct siginfo_t {
+    pub si_signo: c_int,
+    pub si_errno: c_int,
+    pub si_code: c_int,
+    _pad: c_int,
+    pub si_addr: *mut c_void,
+    _rest: [u64; 13]
+}
+
+#[repr(C)]
+pub struct sigaction_t {
+    /// Either a `sa_handler` or, with `SA_SIGINFO`, a `sa_sigaction`
+    pub sa_sigaction: usize,
+    pub sa_mask: sigset_t,
+    pub sa_flags: c_int,
+    pub sa_restorer: Option<extern "C" fn()>
+}
+
+#[repr(C)]
+pub struct stack_t {
+    pub ss_sp: *mut c_void,
+    pub ss_flags: c_int,
+    pub ss_size: usize
+}
+
+#[link(name="c")]
+extern "C" {
+    pub fn sigaction(
+        signum: c_int,
+        act: *const sigaction_t,
+        oldact: *mut sigaction_t
+    ) -> c_int;
+    pub fn sigemptyset(set: *mut sigset_t) -> c_int;
+    pub fn sigaltstack(ss: *const stack_t, old_ss: *mut stack_t) -> c_int;
+    pub fn raise(sig: c_int) -> c_int;
+}
@@ -0,0 +1,67 @@
+#![allow(non_camel_case_types)]
+
+use crate::{time::time_t, types::*};
+
+pub type mode_t = c_uint;
+
+pub const S_IFMT: mode_t = 0o170000;
+pub const S_IFDIR: mode_t = 0o040000;
+pub const S_IFREG: mode_t = 0o100000;
+pub const S_IFLNK: mode_t = 0o120000;
+
+#[cfg(target_arch = "x86_64")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct stat {
+    pub st_dev: c_ulong,
+    pub st_ino: c_ulong,
+    pub st_nlink: c_ulong,
+    pub st_mode: mode_t,
+    pub st_uid: c_uint,
+    pub st_gid: c_uint,
+    __pad0: c_int,
+    pub st_rdev: c_ulong,
+    pub st_size: c_long,
+    pub st_blksize: c_long,
+    pub st_blocks: c_long,
+    pub st_atime: time_t,
+    pub st_atime_nsec: c_long,
+    pub st_mtime: time_t,
+    pub st_mtime_nsec: c_long,
+    pub st_ctime: time_t,
+    pub st_ctime_nsec: c_long,
+    __unused: [c_long; 3]
+}
+
+#[cfg(target_arch = "aarch64")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct stat {
+    pub st_dev: c_ulong,
+    pub st_ino: c_ulong,
+    pub st_mode: mode_t,
+    pub st_nlink: c_uint,
+    pub st_uid: c_uint,
+    pub st_gid: c_uint,
+    pub st_rdev: c_ulong,
+    __pad1: c_ulong,
+    pub st_size: c_long,
+    pub st_blksize: c_int,
+    __pad2: c_int,
+    pub st_blocks: c_long,
+    pub st_atime: time_t,
+    pub st_atime_nsec: c_long,
+    pub st_mtime: time_t,
+    pub st_mtime_nsec: c_long,
+    pub st_ctime: time_t,
+    pub st_ctime_nsec: c_long,
+    __unused: [c_int; 2]
+}
+
+#[link(name="c")]
+extern "C" {
+    pub fn stat(pathname: *const c_char, statbuf: *mut stat) -> c_int;
+    pub fn fstat(fd: c_int, statbuf: *mut stat) -> c_int;
+    pub fn lstat(pathname: *const c_char, statbuf: *mut stat) -> c_int;
+    pub fn mkdir(pathname: *const c_char, mode: mode_t) -> c_int;
+}
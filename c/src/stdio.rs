@@ -4,10 +4,16 @@ use crate::types::*;
 #[cfg(target_os="linux")] pub const STDOUT: c_int = 1;
 #[cfg(target_os="linux")] pub const STDERR: c_int = 2;
 
+pub const SEEK_SET: c_int = 0;
+pub const SEEK_CUR: c_int = 1;
+pub const SEEK_END: c_int = 2;
+
 #[link(name="c")]
-extern {
-    pub fn write(fd: c_int, buf: *const c_void, count: usize) -> c_int;
+extern "C" {
+    pub fn write(fd: c_int, buf: *const c_void, count: usize) -> isize;
     pub fn fopen(pathname: *const c_char, mode: *const c_char) -> *mut c_void;
+    pub fn fdopen(fd: c_int, mode: *const c_char) -> *mut c_void;
+    pub fn fileno(stream: *mut c_void) -> c_int;
     pub fn fread(
         ptr: *mut c_void,
         size: usize,
@@ -20,6 +26,12 @@ extern {
         nmemb: usize,
         stream: *mut c_void
     ) -> usize;
+    pub fn fflush(stream: *mut c_void) -> c_int;
+    pub fn ferror(stream: *mut c_void) -> c_int;
+    pub fn clearerr(stream: *mut c_void);
+    pub fn fseeko(stream: *mut c_void, offset: i64, whence: c_int) -> c_int;
+    pub fn ftello(stream: *mut c_void) -> i64;
     pub fn fclose(stream: *mut c_void) -> c_int;
+    pub fn rename(oldpath: *const c_char, newpath: *const c_char) -> c_int;
 }
 
@@ -1,9 +1,15 @@
 use crate::types::*;
 
 #[link(name="c")]
-extern {
+extern "C" {
     pub fn abort() -> !;
+    pub fn atexit(function: extern "C" fn()) -> c_int;
     pub fn free(ptr: *mut c_void);
+    pub fn calloc(nmemb: usize, size: usize) -> *mut c_void;
+    pub fn realloc(ptr: *mut c_void, size: usize) -> *mut c_void;
+    pub fn getenv(name: *const c_char) -> *mut c_char;
+    pub fn setenv(name: *const c_char, value: *const c_char, overwrite: c_int) -> c_int;
+    pub fn unsetenv(name: *const c_char) -> c_int;
 
     #[cfg(target_os="linux")]
     pub fn posix_memalign(
@@ -11,4 +17,8 @@ extern {
         alignment: usize,
         size: usize
     ) -> c_int;
+    #[cfg(target_os="linux")]
+    pub fn mkstemp(template: *mut c_char) -> c_int;
+    #[cfg(target_os="linux")]
+    pub fn mkdtemp(template: *mut c_char) -> *mut c_char;
 }
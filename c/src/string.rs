@@ -0,0 +1,8 @@
+use crate::types::*;
+
+#[link(name="c")]
+extern "C" {
+    /// glibc's version, which returns the message. That may be a static
+    /// string rather than `buf`
+    pub fn strerror_r(errnum: c_int, buf: *mut c_char, buflen: usize) -> *mut c_char;
+}
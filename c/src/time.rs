@@ -0,0 +1,30 @@
+#![allow(non_camel_case_types)]
+
+use crate::types::*;
+
+pub type time_t = c_long;
+pub type clockid_t = c_int;
+
+pub const CLOCK_REALTIME: clockid_t = 0;
+pub const CLOCK_MONOTONIC: clockid_t = 1;
+
+pub const TIMER_ABSTIME: c_int = 1;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct timespec {
+    pub tv_sec: time_t,
+    pub tv_nsec: c_long
+}
+
+#[link(name="c")]
+extern "C" {
+    pub fn clock_gettime(clockid: clockid_t, tp: *mut timespec) -> c_int;
+    pub fn nanosleep(req: *const timespec, rem: *mut timespec) -> c_int;
+    pub fn clock_nanosleep(
+        clockid: clockid_t,
+        flags: c_int,
+        req: *const timespec,
+        rem: *mut timespec
+    ) -> c_int;
+}
@@ -1,6 +1,14 @@
 use crate::types::*;
 
+pub const _SC_PAGESIZE: c_int = 30;
+pub const _SC_NPROCESSORS_ONLN: c_int = 84;
+
 #[link(name="c")]
-extern {
+extern "C" {
+    pub fn read(fd: c_int, buf: *mut c_void, count: usize) -> isize;
     pub fn write(fd: c_int, buf: *const c_void, count: usize) -> isize;
+    pub fn close(fd: c_int) -> c_int;
+    pub fn unlink(pathname: *const c_char) -> c_int;
+    pub fn rmdir(pathname: *const c_char) -> c_int;
+    pub fn sysconf(name: c_int) -> c_long;
 }
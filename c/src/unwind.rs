@@ -0,0 +1,24 @@
+#![allow(non_camel_case_types)]
+
+use crate::types::*;
+
+pub type _Unwind_Reason_Code = c_int;
+
+pub const _URC_NO_REASON: _Unwind_Reason_Code = 0;
+pub const _URC_END_OF_STACK: _Unwind_Reason_Code = 5;
+
+#[repr(C)]
+pub struct _Unwind_Context {
+    _private: [u8; 0]
+}
+
+pub type _Unwind_Trace_Fn = extern "C" fn(
+    context: *mut _Unwind_Context,
+    arg: *mut c_void
+) -> _Unwind_Reason_Code;
+
+#[link(name="gcc_s")]
+extern "C" {
+    pub fn _Unwind_Backtrace(trace: _Unwind_Trace_Fn, arg: *mut c_void) -> _Unwind_Reason_Code;
+    pub fn _Unwind_GetIP(context: *mut _Unwind_Context) -> usize;
+}
@@ -1,7 +1,16 @@
 #![no_std]
+// Fields are spelled out in struct literals throughout the crate
+#![allow(clippy::redundant_field_names)]
 
 use c::types::*;
+use lstd::{
+    backtrace::Symbol,
+    error::Error,
+    ffi::CStr,
+    string::String
+};
 use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
     ops::Drop,
     ptr::null_mut
 };
@@ -9,38 +18,86 @@ use core::{
 pub const RTLD_NOW: c_int = 2;
 
 #[link(name = "dl")]
-extern {
+extern "C" {
     fn dlopen(file: *const c_char, mode: c_int) -> *mut c_void;
     fn dlclose(handle: *mut c_void) -> c_int;
     fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    fn dlerror() -> *mut c_char;
+    fn dladdr(addr: *const c_void, info: *mut DlInfo) -> c_int;
+}
+
+#[repr(C)]
+struct DlInfo {
+    dli_fname: *const c_char,
+    dli_fbase: *mut c_void,
+    dli_sname: *const c_char,
+    dli_saddr: *mut c_void
 }
 
 pub struct SharedLibrary {
     handle: *mut c_void
 }
 
+/// Why `dlopen` or `dlsym` failed, as described by `dlerror`
+#[derive(Debug)]
+pub struct DlError {
+    message: String
+}
+
 impl SharedLibrary {
-    pub fn open(filename: *const c_char) -> Option<SharedLibrary> {
+    pub fn open(filename: &CStr) -> Result<SharedLibrary, DlError> {
         let handle = unsafe {
-            dlopen(filename, RTLD_NOW)
+            dlopen(filename.as_ptr(), RTLD_NOW)
         };
         match handle {
-            h if h == null_mut() => None,
-            h => Some(SharedLibrary { handle: h })
+            h if h.is_null() => Err(DlError::last("could not open library")),
+            h => Ok(SharedLibrary { handle: h })
         }
     }
 
-    pub fn symbol(&self, symbol: *const c_char) -> Option<*mut c_void> {
+    pub fn symbol(&self, symbol: &CStr) -> Result<*mut c_void, DlError> {
         let symbol = unsafe {
-            dlsym(self.handle, symbol)
+            // Clear any earlier error so the one read below is ours
+            dlerror();
+            dlsym(self.handle, symbol.as_ptr())
         };
         match symbol {
-            h if h == null_mut() => None,
-            _ => Some(symbol)
+            h if h.is_null() => Err(DlError::last("symbol not found")),
+            _ => Ok(symbol)
         }
     }
 }
 
+/// Finds the loaded object and exported symbol `address` falls in and
+/// passes them to `found`, for `lstd::backtrace::set_resolver`. Functions
+/// in an executable are only found if it was linked with `-rdynamic`
+pub fn symbolize(address: usize, found: &mut dyn FnMut(&Symbol<'_>)) {
+    let mut info = DlInfo {
+        dli_fname: null_mut(),
+        dli_fbase: null_mut(),
+        dli_sname: null_mut(),
+        dli_saddr: null_mut()
+    };
+    if unsafe { dladdr(address as *const c_void, &mut info) } == 0 {
+        return;
+    }
+
+    // The names live in the object's string tables, which stay put as
+    // long as it is loaded. Only lending them to `found` keeps them from
+    // outliving a `SharedLibrary` that closes it
+    let name = |s: *const c_char| if s.is_null() {
+        None
+    } else {
+        Some(unsafe { CStr::from_ptr(s) })
+    };
+    found(&Symbol {
+        name: name(info.dli_sname),
+        address: info.dli_saddr as usize,
+        object: name(info.dli_fname),
+        object_base: info.dli_fbase as usize
+    });
+}
+
 impl Drop for SharedLibrary {
     fn drop(&mut self) {
         unsafe {
@@ -48,3 +105,29 @@ impl Drop for SharedLibrary {
         }
     }
 }
+
+impl DlError {
+    /// Takes the error `dlerror` has waiting, falling back to `default`
+    /// when there is none
+    fn last(default: &str) -> DlError {
+        let message = unsafe { dlerror() };
+        let message = if message.is_null() {
+            String::from(default)
+        } else {
+            unsafe { CStr::from_ptr(message) }.to_string_lossy()
+        };
+        DlError { message: message }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for DlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for DlError {}
@@ -1,61 +1,300 @@
-use crate::stdlib::{free, posix_memalign};
+mod arena;
+mod guard;
+mod pool;
+pub(crate) mod spin;
+#[cfg(test)]
+pub(crate) mod testing;
+mod tracking;
+#[cfg(feature = "track-allocations")]
+mod tracker;
+
+#[cfg(not(all(feature = "guard-pages", debug_assertions)))]
+mod heap;
+
+// Debug builds can swap the C heap for the guard page allocator
+#[cfg(not(all(feature = "guard-pages", debug_assertions)))]
+use heap as sys;
+#[cfg(all(feature = "guard-pages", debug_assertions))]
+use guard as sys;
+
+pub use arena::*;
+pub use guard::GuardPage;
+pub use pool::*;
+pub use tracking::*;
+#[cfg(feature = "track-allocations")]
+pub use tracker::report_leaks;
+
+use crate::{
+    abort,
+    container::Array,
+    eprintln
+};
 use core::{
-    alloc::Layout,
-    mem::MaybeUninit,
-    ops::{Deref, DerefMut, Drop}
+    alloc::{GlobalAlloc, Layout},
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    mem::{forget, transmute},
+    ops::{Deref, DerefMut, Drop},
+    pin::Pin,
+    ptr::{copy_nonoverlapping, drop_in_place, null_mut, read, NonNull},
+    sync::atomic::{AtomicPtr, Ordering}
 };
 
-pub struct Box<T> {
-    data: *mut T
+#[cfg(feature = "track-allocations")]
+use core::panic::Location;
+
+/// A source of memory for `Box`, `Array` and `Rc`. Containers never ask for
+/// zero-sized blocks, so implementations do not need to handle them
+///
+/// # Safety
+///
+/// A block returned by `allocate` or `reallocate` must be at least as large
+/// and as aligned as the layout asked for, must not overlap any other live
+/// block, and must stay valid until it is passed to `deallocate` or
+/// `reallocate`, even if the allocator itself is moved. Those two must
+/// accept any block this allocator, or a clone of it, handed out, along
+/// with the layout it was last allocated or resized with
+pub unsafe trait Allocator {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// # Safety
+    ///
+    /// `ptr` must be a live block from this allocator, or a clone of it,
+    /// and `layout` the one it was allocated or last resized with
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// Resizes a block to `new_size` bytes, keeping its alignment and
+    /// contents. The default moves the contents to a new block
+    ///
+    /// # Safety
+    ///
+    /// As for `deallocate`. `new_size` must not be zero, and on success the
+    /// old block must no longer be used
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize
+    ) -> Option<NonNull<u8>> {
+        let new_layout = Layout::from_size_align_unchecked(
+            new_size,
+            layout.align()
+        );
+        let new_ptr = self.allocate(new_layout)?;
+        let n = if layout.size() < new_size { layout.size() } else { new_size };
+        copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), n);
+        self.deallocate(ptr, layout);
+
+        Some(new_ptr)
+    }
+}
+
+unsafe impl<A: Allocator + ?Sized> Allocator for &A {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        (**self).allocate(layout)
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).deallocate(ptr, layout)
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize
+    ) -> Option<NonNull<u8>> {
+        (**self).reallocate(ptr, layout, new_size)
+    }
 }
 
+pub struct Box<T: ?Sized, A: Allocator = Malloc> {
+    data: *mut T,
+    alloc: A
+}
+
+unsafe impl<T: ?Sized + Send, A: Allocator + Send> Send for Box<T, A> {}
+unsafe impl<T: ?Sized + Sync, A: Allocator + Sync> Sync for Box<T, A> {}
+
 impl<T> Box<T> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
     pub fn new(value: T) -> Box<T> {
-        let data = alloc::<T>(1).unwrap();
+        Box::new_in(value, Malloc)
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn try_new(value: T) -> Option<Box<T>> {
+        Box::try_new_in(value, Malloc)
+    }
+
+    /// Boxes `value` and pins it, since it can never move out of the box
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn pin(value: T) -> Pin<Box<T>> {
+        Box::into_pin(Box::new(value))
+    }
+}
+
+impl<T: ?Sized> Box<T> {
+    /// # Safety
+    ///
+    /// See `from_raw_in`
+    pub unsafe fn from_raw(ptr: *mut T) -> Box<T> {
+        Box::from_raw_in(ptr, Malloc)
+    }
+}
+
+impl<T> Box<[T]> {
+    /// Moves the elements of `array` into a boxed slice, giving back any
+    /// spare capacity
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn from_array(array: Array<T>) -> Box<[T]> {
+        array.into_boxed_slice()
+    }
+}
+
+impl<T, A: Allocator> Box<T, A> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn new_in(value: T, alloc: A) -> Box<T, A> {
+        match Box::try_new_in(value, alloc) {
+            Some(b) => b,
+            None => handle_alloc_error(Layout::new::<T>())
+        }
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn try_new_in(value: T, alloc: A) -> Option<Box<T, A>> {
+        let layout = Layout::new::<T>();
+        let data: NonNull<T> = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            alloc.allocate(layout)?.cast()
+        };
         unsafe {
-            data.write(value);
+            data.as_ptr().write(value);
         }
 
-        Box { data: data }
+        Some(Box { data: data.as_ptr(), alloc: alloc })
     }
 
-    pub fn try_new(value: T) -> Option<Box<T>> {
-        match alloc::<T>(1) {
-            None => None,
-            Some(data) => {
-                unsafe {
-                    data.write(value);
-                    Some(Box { data: data })
-                }
-            }
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn pin_in(value: T, alloc: A) -> Pin<Box<T, A>> {
+        Box::into_pin(Box::new_in(value, alloc))
+    }
+
+    pub fn into_inner(this: Box<T, A>) -> T {
+        let (data, alloc) = Box::into_raw_with_allocator(this);
+        unsafe {
+            let value = data.read();
+            dealloc_in(&alloc, data, Layout::new::<T>());
+            value
         }
     }
+}
+
+impl<T, A: Allocator> Box<[T], A> {
+    pub fn from_array_in(array: Array<T, A>) -> Box<[T], A> {
+        array.into_boxed_slice()
+    }
+
+    /// Turns the boxed slice back into an array without copying
+    pub fn into_array(this: Box<[T], A>) -> Array<T, A> {
+        Array::from_boxed_slice(this)
+    }
+}
 
-    pub fn from_raw(ptr: *mut T) -> Box<T> {
-        Box { data: ptr }
+impl<A: Allocator> Box<str, A> {
+    /// Reinterprets boxed bytes as a string
+    ///
+    /// # Safety
+    ///
+    /// The bytes must be valid UTF-8
+    pub unsafe fn from_utf8_unchecked(bytes: Box<[u8], A>) -> Box<str, A> {
+        let (data, alloc) = Box::into_raw_with_allocator(bytes);
+        Box::from_raw_in(data as *mut str, alloc)
     }
 
-    pub fn into_raw(this: Box<T>) -> *mut T {
-        let result = this.data;
-        core::mem::forget(this);
+    pub fn into_boxed_bytes(this: Box<str, A>) -> Box<[u8], A> {
+        let (data, alloc) = Box::into_raw_with_allocator(this);
+        unsafe { Box::from_raw_in(data as *mut [u8], alloc) }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Box<T, A> {
+    /// Takes ownership of a value, freeing it through `alloc` when the box
+    /// is dropped
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to an initialised value that `alloc` allocated with
+    /// the value's layout, as from `into_raw_with_allocator`, and nothing
+    /// else may use or free it afterwards
+    pub unsafe fn from_raw_in(ptr: *mut T, alloc: A) -> Box<T, A> {
+        Box { data: ptr, alloc: alloc }
+    }
+
+    pub fn into_raw(this: Box<T, A>) -> *mut T {
+        Box::into_raw_with_allocator(this).0
+    }
+
+    pub fn into_raw_with_allocator(this: Box<T, A>) -> (*mut T, A) {
+        let result = (this.data, unsafe { read(&this.alloc) });
+        forget(this);
         result
     }
 
-    pub fn as_ptr(this: &Box<T>) -> *mut T {
+    pub fn into_pin(this: Box<T, A>) -> Pin<Box<T, A>> {
+        // The value lives on the heap and the box never hands out a way to
+        // move it, so it stays put until it is dropped
+        unsafe { Pin::new_unchecked(this) }
+    }
+
+    pub fn as_ptr(this: &Box<T, A>) -> *mut T {
         this.data
     }
+
+    pub fn allocator(this: &Box<T, A>) -> &A {
+        &this.alloc
+    }
+}
+
+/// Turns a box into a box of an unsized type its contents coerce to, most
+/// often a trait object. This stands in for the unsizing coercion that only
+/// the standard library's `Box` gets, and is checked the same way
+///
+/// ```ignore
+/// let window: Box<dyn Window> = into_dyn!(Box::new(native), dyn Window);
+/// ```
+#[macro_export]
+macro_rules! into_dyn {
+    ($boxed:expr, $target:ty) => {{
+        let (ptr, alloc) = $crate::alloc::Box::into_raw_with_allocator($boxed);
+        let ptr: *mut $target = ptr;
+        // The pointer came out of a box with this allocator just above
+        #[allow(unused_unsafe)]
+        unsafe { $crate::alloc::Box::from_raw_in(ptr, alloc) }
+    }};
+}
+
+/// Frees the memory behind a value without dropping it
+unsafe fn dealloc_in<T: ?Sized, A: Allocator>(alloc: &A, ptr: *mut T, layout: Layout) {
+    if layout.size() != 0 {
+        alloc.deallocate(NonNull::new_unchecked(ptr as *mut u8), layout);
+    }
 }
 
-impl<T> Drop for Box<T> {
+impl<T: ?Sized, A: Allocator> Drop for Box<T, A> {
     fn drop(&mut self) {
         unsafe {
-            core::ptr::drop_in_place(self.data);
+            let layout = Layout::for_value(&*self.data);
+            drop_in_place(self.data);
+            dealloc_in(&self.alloc, self.data, layout);
         }
-        dealloc(self.data);
     }
 }
 
-impl<T> Deref for Box<T> {
+impl<T: ?Sized, A: Allocator> Deref for Box<T, A> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -65,7 +304,7 @@ impl<T> Deref for Box<T> {
     }
 }
 
-impl<T> DerefMut for Box<T> {
+impl<T: ?Sized, A: Allocator> DerefMut for Box<T, A> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         unsafe {
             &mut *self.data
@@ -73,25 +312,239 @@ impl<T> DerefMut for Box<T> {
     }
 }
 
-#[cfg(target_os="linux")]
-pub fn alloc<T>(count: usize) -> Option<*mut T> {
-    let mut ptr: MaybeUninit<*mut T> = MaybeUninit::uninit();
-    unsafe {
-        let layout = Layout::new::<T>();
-        let align = if layout.align() < 8 { 8 } else { layout.align() };
-        let size = count * layout.size();
+impl<T: Clone, A: Allocator + Clone> Clone for Box<T, A> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn clone(&self) -> Box<T, A> {
+        Box::new_in((**self).clone(), self.alloc.clone())
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for Box<[T], A> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn clone(&self) -> Box<[T], A> {
+        Array::from_slice_in(self, self.alloc.clone()).into_boxed_slice()
+    }
+}
+
+impl<A: Allocator + Clone> Clone for Box<str, A> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn clone(&self) -> Box<str, A> {
+        let bytes = Array::from_slice_in(self.as_bytes(), self.alloc.clone());
+        unsafe { Box::from_utf8_unchecked(bytes.into_boxed_slice()) }
+    }
+}
+
+impl<T: Clone> From<&[T]> for Box<[T]> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn from(slice: &[T]) -> Box<[T]> {
+        Array::from_slice(slice).into_boxed_slice()
+    }
+}
+
+impl From<&str> for Box<str> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn from(s: &str) -> Box<str> {
+        let bytes = Box::<[u8]>::from(s.as_bytes());
+        unsafe { Box::from_utf8_unchecked(bytes) }
+    }
+}
+
+impl<T, A: Allocator> From<Array<T, A>> for Box<[T], A> {
+    fn from(array: Array<T, A>) -> Box<[T], A> {
+        array.into_boxed_slice()
+    }
+}
 
-        let rc = posix_memalign(ptr.as_mut_ptr() as *mut *mut _, align, size);
-        if rc != 0 {
-            return None;
+impl<T: ?Sized + Debug, A: Allocator> Debug for Box<T, A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + Display, A: Allocator> Display for Box<T, A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + PartialEq, A: Allocator> PartialEq for Box<T, A> {
+    fn eq(&self, other: &Box<T, A>) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: ?Sized + Eq, A: Allocator> Eq for Box<T, A> {}
+
+/// The C heap allocator. Binaries that want to use the `alloc` crate can
+/// register it with
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: lstd::alloc::Malloc = lstd::alloc::Malloc;
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct Malloc;
+
+unsafe impl Allocator for Malloc {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        NonNull::new(unsafe { GlobalAlloc::alloc(self, layout) })
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        GlobalAlloc::dealloc(self, ptr.as_ptr(), layout)
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize
+    ) -> Option<NonNull<u8>> {
+        NonNull::new(GlobalAlloc::realloc(self, ptr.as_ptr(), layout, new_size))
+    }
+}
+
+unsafe impl GlobalAlloc for Malloc {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = sys::alloc(layout);
+        #[cfg(feature = "track-allocations")]
+        tracker::on_alloc(ptr, layout, Location::caller());
+        ptr
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = sys::alloc_zeroed(layout);
+        #[cfg(feature = "track-allocations")]
+        tracker::on_alloc(ptr, layout, Location::caller());
+        ptr
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        #[cfg(feature = "track-allocations")]
+        if !tracker::on_dealloc(ptr, Some(layout), Location::caller()) {
+            return;
         }
+        sys::dealloc(ptr, layout);
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize
+    ) -> *mut u8 {
+        let new_ptr = sys::realloc(ptr, layout, new_size);
+        #[cfg(feature = "track-allocations")]
+        tracker::on_realloc(ptr, layout, new_ptr, new_size, Location::caller());
+        new_ptr
+    }
+}
+
+static ALLOC_ERROR_HOOK: AtomicPtr<()> = AtomicPtr::new(null_mut());
+
+/// Installs a function that is called before aborting when an allocation
+/// cannot be satisfied. The `alloc` crate reports its own failures through
+/// the panic handler instead
+pub fn set_alloc_error_hook(hook: fn(Layout)) {
+    ALLOC_ERROR_HOOK.store(hook as *mut (), Ordering::SeqCst);
+}
+
+/// Removes the installed hook, returning it, and restores the default one
+pub fn take_alloc_error_hook() -> fn(Layout) {
+    let hook = ALLOC_ERROR_HOOK.swap(null_mut(), Ordering::SeqCst);
+    if hook.is_null() {
+        default_alloc_error_hook
+    } else {
+        unsafe { transmute::<*mut (), fn(Layout)>(hook) }
+    }
+}
+
+fn default_alloc_error_hook(layout: Layout) {
+    eprintln!(
+        "memory allocation of {} bytes (align {}) failed",
+        layout.size(),
+        layout.align()
+    );
+}
+
+pub fn handle_alloc_error(layout: Layout) -> ! {
+    let hook = ALLOC_ERROR_HOOK.load(Ordering::SeqCst);
+    if hook.is_null() {
+        default_alloc_error_hook(layout);
+    } else {
+        unsafe { transmute::<*mut (), fn(Layout)>(hook)(layout); }
+    }
+
+    abort()
+}
+
+/// Panics because a requested capacity does not fit in a `Layout`. Unlike
+/// `handle_alloc_error` this is a caller bug, not the system running out
+#[cold]
+#[track_caller]
+pub fn capacity_overflow() -> ! {
+    panic!("capacity overflow")
+}
+
+#[cfg_attr(feature = "track-allocations", track_caller)]
+pub fn alloc<T>(count: usize) -> Option<*mut T> {
+    let layout = Layout::array::<T>(count).ok()?;
+    let ptr = unsafe { Malloc.alloc(layout) };
+    if ptr.is_null() {
+        None
+    } else {
+        Some(ptr as *mut T)
     }
+}
 
-    Some(unsafe { ptr.assume_init() })
+/// Grows or shrinks an allocation made by `alloc` from `count` to
+/// `new_count` elements, keeping the contents
+#[cfg_attr(feature = "track-allocations", track_caller)]
+pub fn realloc<T>(ptr: *mut T, count: usize, new_count: usize) -> Option<*mut T> {
+    let layout = Layout::array::<T>(count).ok()?;
+    let new_layout = Layout::array::<T>(new_count).ok()?;
+    let new_ptr = unsafe {
+        Malloc.realloc(ptr as *mut u8, layout, new_layout.size())
+    };
+    if new_ptr.is_null() {
+        None
+    } else {
+        Some(new_ptr as *mut T)
+    }
 }
 
-pub fn dealloc<T>(ptr: *mut T) {
-    unsafe {
-        free(ptr as *mut _);
+/// Frees an allocation made by `alloc`
+///
+/// # Safety
+///
+/// `ptr` must have come from `alloc::<T>(count)`, or a `realloc` to
+/// `count`, and not been freed since
+#[cfg_attr(feature = "track-allocations", track_caller)]
+pub unsafe fn dealloc<T>(ptr: *mut T, count: usize) {
+    let layout = match Layout::array::<T>(count) {
+        Ok(layout) => layout,
+        // `alloc` could never have handed out such a block
+        Err(_) => abort()
+    };
+
+    #[cfg(feature = "track-allocations")]
+    if !tracker::on_dealloc(ptr as *mut u8, Some(layout), Location::caller()) {
+        return;
     }
+
+    sys::dealloc(ptr as *mut u8, layout);
+}
+
+/// Prints every allocation that is still live. Without the
+/// `track-allocations` feature nothing is recorded and this returns 0
+#[cfg(not(feature = "track-allocations"))]
+pub fn report_leaks() -> usize {
+    0
 }
@@ -0,0 +1,272 @@
+use crate::{
+    alloc::{alloc, capacity_overflow, dealloc, handle_alloc_error, Allocator, Box},
+    container::Array
+};
+use core::{
+    alloc::Layout,
+    cell::Cell,
+    mem::size_of,
+    ops::Drop,
+    ptr::{copy_nonoverlapping, null_mut, NonNull},
+    slice::from_raw_parts_mut
+};
+
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Chunks are kept in a singly linked list. The header sits at the start of
+/// each block and the usable space follows it
+struct Chunk {
+    next: *mut Chunk,
+    size: usize
+}
+
+impl Chunk {
+    fn start(chunk: *mut Chunk) -> usize {
+        chunk as usize + size_of::<Chunk>()
+    }
+
+    fn end(chunk: *mut Chunk) -> usize {
+        unsafe { Chunk::start(chunk) + (*chunk).size }
+    }
+}
+
+/// A bump allocator for short-lived memory. Allocations are carved out of
+/// large chunks and are all released at once by `reset`, which keeps the
+/// chunks around for the next round. Destructors are not run for values
+/// placed with `alloc`; use `alloc_box` or `ArenaArray` for types that need
+/// dropping. `&Arena` can also be handed to any container as its allocator
+pub struct Arena {
+    chunk_size: usize,
+    first: Cell<*mut Chunk>,
+    current: Cell<*mut Chunk>,
+    cursor: Cell<usize>,
+    end: Cell<usize>
+}
+
+#[allow(clippy::mut_from_ref)]
+impl Arena {
+    pub fn new() -> Arena {
+        Arena::with_chunk_size(DEFAULT_CHUNK_SIZE)
+    }
+
+    pub fn with_chunk_size(chunk_size: usize) -> Arena {
+        Arena {
+            chunk_size: chunk_size,
+            first: Cell::new(null_mut()),
+            current: Cell::new(null_mut()),
+            cursor: Cell::new(0),
+            end: Cell::new(0)
+        }
+    }
+
+    /// Releases every allocation made from the arena. Requiring `&mut self`
+    /// guarantees that nothing borrowed from the arena is still alive
+    pub fn reset(&mut self) {
+        let first = self.first.get();
+        self.current.set(first);
+        if first.is_null() {
+            self.cursor.set(0);
+            self.end.set(0);
+        } else {
+            self.cursor.set(Chunk::start(first));
+            self.end.set(Chunk::end(first));
+        }
+    }
+
+    /// Total number of bytes reserved from the system by this arena
+    pub fn capacity(&self) -> usize {
+        let mut total = 0;
+        let mut chunk = self.first.get();
+        while !chunk.is_null() {
+            unsafe {
+                total += (*chunk).size;
+                chunk = (*chunk).next;
+            }
+        }
+
+        total
+    }
+
+    pub fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        if let Some(ptr) = self.bump(layout) {
+            return ptr;
+        }
+
+        self.next_chunk(layout);
+        self.bump(layout).unwrap()
+    }
+
+    pub fn alloc<T>(&self, value: T) -> &mut T {
+        let ptr = self.alloc_layout(Layout::new::<T>()).as_ptr() as *mut T;
+        unsafe {
+            ptr.write(value);
+            &mut *ptr
+        }
+    }
+
+    pub fn alloc_box<T>(&self, value: T) -> ArenaBox<'_, T> {
+        Box::new_in(value, self)
+    }
+
+    pub fn alloc_slice_copy<T: Copy>(&self, slice: &[T]) -> &mut [T] {
+        let ptr = self.alloc_array_raw::<T>(slice.len());
+        unsafe {
+            copy_nonoverlapping(slice.as_ptr(), ptr, slice.len());
+            from_raw_parts_mut(ptr, slice.len())
+        }
+    }
+
+    pub fn alloc_slice_clone<T: Clone>(&self, slice: &[T]) -> &mut [T] {
+        self.alloc_slice_fill_with(slice.len(), |i| slice[i].clone())
+    }
+
+    pub fn alloc_slice_fill_with<T, F: FnMut(usize) -> T>(
+        &self,
+        len: usize,
+        mut f: F
+    ) -> &mut [T] {
+        let ptr = self.alloc_array_raw::<T>(len);
+        for i in 0..len {
+            unsafe { ptr.add(i).write(f(i)); }
+        }
+
+        unsafe { from_raw_parts_mut(ptr, len) }
+    }
+
+    pub fn alloc_str(&self, s: &str) -> &mut str {
+        let bytes = self.alloc_slice_copy(s.as_bytes());
+        unsafe { core::str::from_utf8_unchecked_mut(bytes) }
+    }
+
+    fn alloc_array_raw<T>(&self, len: usize) -> *mut T {
+        let layout = match Layout::array::<T>(len) {
+            Ok(layout) => layout,
+            Err(_) => capacity_overflow()
+        };
+        self.alloc_layout(layout).as_ptr() as *mut T
+    }
+
+    /// Resizes the most recent allocation in place if there is room left in
+    /// the current chunk
+    fn grow_in_place(&self, ptr: *mut u8, size: usize, new_size: usize) -> bool {
+        let ptr = ptr as usize;
+        if ptr + size != self.cursor.get() { return false; }
+        if ptr + new_size > self.end.get() { return false; }
+
+        self.cursor.set(ptr + new_size);
+        true
+    }
+
+    fn bump(&self, layout: Layout) -> Option<NonNull<u8>> {
+        if self.current.get().is_null() { return None; }
+
+        let align = layout.align();
+        let start = self.cursor.get().checked_add(align - 1)? & !(align - 1);
+        let end = start.checked_add(layout.size())?;
+        if end > self.end.get() { return None; }
+
+        self.cursor.set(end);
+        NonNull::new(start as *mut u8)
+    }
+
+    /// Moves to the next chunk that can hold `layout`, allocating one if
+    /// the chunks kept from previous rounds are too small
+    fn next_chunk(&self, layout: Layout) {
+        let needed = layout.size() + layout.align();
+        let current = self.current.get();
+        let next = if current.is_null() {
+            self.first.get()
+        } else {
+            unsafe { (*current).next }
+        };
+
+        let chunk = if !next.is_null() && unsafe { (*next).size } >= needed {
+            next
+        } else {
+            let size = if needed > self.chunk_size { needed } else { self.chunk_size };
+            let chunk = match alloc::<u8>(size_of::<Chunk>() + size) {
+                Some(ptr) => ptr as *mut Chunk,
+                None => handle_alloc_error(layout)
+            };
+            unsafe {
+                chunk.write(Chunk { next: next, size: size });
+            }
+
+            if current.is_null() {
+                self.first.set(chunk);
+            } else {
+                unsafe { (*current).next = chunk; }
+            }
+            chunk
+        };
+
+        self.current.set(chunk);
+        self.cursor.set(Chunk::start(chunk));
+        self.end.set(Chunk::end(chunk));
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        let mut chunk = self.first.get();
+        while !chunk.is_null() {
+            unsafe {
+                let next = (*chunk).next;
+                // Allocated as bytes, with the chunk's size after its header
+                dealloc(chunk as *mut u8, size_of::<Chunk>() + (*chunk).size);
+                chunk = next;
+            }
+        }
+    }
+}
+
+impl Default for Arena {
+    fn default() -> Arena {
+        Arena::new()
+    }
+}
+
+unsafe impl Allocator for Arena {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        Some(self.alloc_layout(layout))
+    }
+
+    /// Memory is only given back if this was the most recent allocation;
+    /// everything else waits for `reset`
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        let ptr = ptr.as_ptr() as usize;
+        if ptr + layout.size() == self.cursor.get() {
+            self.cursor.set(ptr);
+        }
+    }
+
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize
+    ) -> Option<NonNull<u8>> {
+        if self.grow_in_place(ptr.as_ptr(), layout.size(), new_size) {
+            return Some(ptr);
+        }
+        if new_size <= layout.size() {
+            return Some(ptr);
+        }
+
+        let new_layout = Layout::from_size_align_unchecked(
+            new_size,
+            layout.align()
+        );
+        let new_ptr = self.alloc_layout(new_layout);
+        copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), layout.size());
+        Some(new_ptr)
+    }
+}
+
+/// An owning handle to a value in an arena. The value is dropped with the
+/// handle, while its memory is reclaimed when the arena is reset
+pub type ArenaBox<'a, T> = Box<T, &'a Arena>;
+
+/// A growable array whose storage comes from an arena. Create one with
+/// `Array::new_in(&arena)`
+pub type ArenaArray<'a, T> = Array<T, &'a Arena>;
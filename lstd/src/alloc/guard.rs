@@ -0,0 +1,192 @@
+use crate::{
+    abort,
+    alloc::{spin::SpinLock, Allocator},
+    eprintln
+};
+use c::{
+    mman::*,
+    unistd::{sysconf, _SC_PAGESIZE}
+};
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    mem::{align_of, size_of},
+    ptr::{copy_nonoverlapping, null_mut, write_bytes, NonNull},
+    sync::atomic::{AtomicUsize, Ordering}
+};
+
+/// Number of freed mappings kept inaccessible before they are unmapped
+const QUARANTINE_SIZE: usize = 256;
+
+const ALLOC_POISON: u8 = 0xaa;
+const FREE_POISON: u8 = 0xdd;
+const MAGIC: usize = 0x6761_7264_7061_6765;
+
+/// Sits right before every block handed out
+struct Header {
+    base: usize,
+    len: usize,
+    size: usize,
+    magic: usize
+}
+
+struct Quarantine {
+    mappings: [(usize, usize); QUARANTINE_SIZE],
+    next: usize
+}
+
+static QUARANTINE: SpinLock<Quarantine> = SpinLock::new(Quarantine {
+    mappings: [(0, 0); QUARANTINE_SIZE],
+    next: 0
+});
+
+static PAGE_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+/// A debugging allocator that gives every block its own mapping and places
+/// it against an inaccessible guard page, so running off the end of a block
+/// faults on the spot. New blocks are filled with 0xaa and freed ones with
+/// 0xdd before being made inaccessible and quarantined, so reading stale
+/// memory faults too. It is slow and wasteful; the `guard-pages` feature
+/// makes it the default allocator in debug builds
+#[derive(Clone, Copy, Default)]
+pub struct GuardPage;
+
+fn page_size() -> usize {
+    let size = PAGE_SIZE.load(Ordering::Relaxed);
+    if size != 0 { return size; }
+
+    let size = unsafe { sysconf(_SC_PAGESIZE) } as usize;
+    PAGE_SIZE.store(size, Ordering::Relaxed);
+    size
+}
+
+fn round_up(n: usize, align: usize) -> usize {
+    (n + align - 1) & !(align - 1)
+}
+
+fn header_of(ptr: *mut u8) -> *mut Header {
+    let header = ptr as usize - size_of::<Header>();
+    (header & !(align_of::<Header>() - 1)) as *mut Header
+}
+
+pub(crate) unsafe fn alloc(layout: Layout) -> *mut u8 {
+    let page = page_size();
+
+    // Enough for the header, the block and any padding that aligning them
+    // needs. The block itself is only aligned as much as it asks for, so
+    // that it ends as close to the guard page as possible
+    let data = size_of::<Header>() + align_of::<Header>()
+        + layout.size() + layout.align();
+    let len = round_up(data, page) + page;
+
+    let base = mmap(
+        null_mut(),
+        len,
+        PROT_READ | PROT_WRITE,
+        MAP_PRIVATE | MAP_ANONYMOUS,
+        -1,
+        0
+    );
+    if base == MAP_FAILED { return null_mut(); }
+
+    let base = base as usize;
+    let guard = base + len - page;
+    if mprotect(guard as *mut _, page, PROT_NONE) != 0 {
+        munmap(base as *mut _, len);
+        return null_mut();
+    }
+
+    let ptr = (guard - layout.size()) & !(layout.align() - 1);
+    let header = header_of(ptr as *mut u8);
+    header.write(Header {
+        base: base,
+        len: len,
+        size: layout.size(),
+        magic: MAGIC
+    });
+
+    write_bytes(ptr as *mut u8, ALLOC_POISON, layout.size());
+    ptr as *mut u8
+}
+
+pub(crate) unsafe fn alloc_zeroed(layout: Layout) -> *mut u8 {
+    let ptr = alloc(layout);
+    if !ptr.is_null() {
+        write_bytes(ptr, 0, layout.size());
+    }
+    ptr
+}
+
+/// Freeing a block twice faults on the header, which is inaccessible by
+/// then
+pub(crate) unsafe fn dealloc(ptr: *mut u8, _: Layout) {
+    if ptr.is_null() { return; }
+
+    let header = header_of(ptr);
+    if (*header).magic != MAGIC {
+        eprintln!("guard page allocator: freeing a block it did not allocate");
+        abort();
+    }
+
+    let Header { base, len, size, .. } = header.read();
+    write_bytes(ptr, FREE_POISON, size);
+    (*header).magic = 0;
+    mprotect(base as *mut _, len, PROT_NONE);
+
+    let evicted = QUARANTINE.with(|q| {
+        let evicted = q.mappings[q.next];
+        q.mappings[q.next] = (base, len);
+        q.next = (q.next + 1) % QUARANTINE_SIZE;
+        evicted
+    });
+    if evicted.0 != 0 {
+        munmap(evicted.0 as *mut _, evicted.1);
+    }
+}
+
+pub(crate) unsafe fn realloc(ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+    let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+    let new_ptr = alloc(new_layout);
+    if !new_ptr.is_null() {
+        let n = if layout.size() < new_size { layout.size() } else { new_size };
+        copy_nonoverlapping(ptr, new_ptr, n);
+        dealloc(ptr, layout);
+    }
+    new_ptr
+}
+
+unsafe impl Allocator for GuardPage {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        NonNull::new(unsafe { alloc(layout) })
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        dealloc(ptr.as_ptr(), layout)
+    }
+
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize
+    ) -> Option<NonNull<u8>> {
+        NonNull::new(realloc(ptr.as_ptr(), layout, new_size))
+    }
+}
+
+unsafe impl GlobalAlloc for GuardPage {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        realloc(ptr, layout, new_size)
+    }
+}
@@ -0,0 +1,71 @@
+use crate::stdlib::{calloc, free, posix_memalign, realloc as c_realloc};
+use core::{
+    alloc::Layout,
+    mem::{size_of, MaybeUninit},
+    ptr::{copy_nonoverlapping, null_mut, write_bytes}
+};
+
+/// malloc() on the supported targets hands out blocks aligned to at least
+/// this many bytes
+const MIN_ALIGN: usize = 16;
+
+pub(crate) unsafe fn alloc(layout: Layout) -> *mut u8 {
+    // posix_memalign() requires a multiple of sizeof(void *)
+    let align = if layout.align() < size_of::<usize>() {
+        size_of::<usize>()
+    } else {
+        layout.align()
+    };
+
+    let mut ptr: MaybeUninit<*mut u8> = MaybeUninit::uninit();
+    let rc = posix_memalign(
+        ptr.as_mut_ptr() as *mut *mut _,
+        align,
+        layout.size()
+    );
+    if rc != 0 {
+        return null_mut();
+    }
+
+    ptr.assume_init()
+}
+
+pub(crate) unsafe fn alloc_zeroed(layout: Layout) -> *mut u8 {
+    if layout.align() <= MIN_ALIGN {
+        return calloc(1, layout.size()) as *mut u8;
+    }
+
+    let ptr = alloc(layout);
+    if !ptr.is_null() {
+        write_bytes(ptr, 0, layout.size());
+    }
+    ptr
+}
+
+pub(crate) unsafe fn dealloc(ptr: *mut u8, _: Layout) {
+    free(ptr as *mut _);
+}
+
+pub(crate) unsafe fn realloc(ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+    if layout.align() <= MIN_ALIGN {
+        return c_realloc(ptr as *mut _, new_size) as *mut u8;
+    }
+
+    // realloc() may hand back a block with weaker alignment, so
+    // over-aligned blocks are moved by hand
+    let new_layout = Layout::from_size_align_unchecked(
+        new_size,
+        layout.align()
+    );
+    let new_ptr = alloc(new_layout);
+    if !new_ptr.is_null() {
+        let n = if layout.size() < new_size {
+            layout.size()
+        } else {
+            new_size
+        };
+        copy_nonoverlapping(ptr, new_ptr, n);
+        dealloc(ptr, layout);
+    }
+    new_ptr
+}
@@ -0,0 +1,153 @@
+use crate::alloc::{capacity_overflow, Allocator, Malloc};
+use core::{
+    alloc::Layout,
+    cell::Cell,
+    mem::{align_of, size_of},
+    ops::Drop,
+    ptr::{null_mut, NonNull}
+};
+
+const DEFAULT_BLOCKS_PER_CHUNK: usize = 64;
+
+struct FreeBlock {
+    next: *mut FreeBlock
+}
+
+struct PoolChunk {
+    next: *mut PoolChunk
+}
+
+/// Hands out fixed-size blocks from a free list. Freed blocks are reused
+/// right away, which suits many short-lived objects of one type. Requests
+/// larger or more aligned than a block fail
+pub struct Pool {
+    block_size: usize,
+    block_align: usize,
+    blocks_per_chunk: usize,
+    free: Cell<*mut FreeBlock>,
+    chunks: Cell<*mut PoolChunk>
+}
+
+impl Pool {
+    pub fn new(layout: Layout) -> Pool {
+        Pool::with_blocks_per_chunk(layout, DEFAULT_BLOCKS_PER_CHUNK)
+    }
+
+    pub fn for_type<T>() -> Pool {
+        Pool::new(Layout::new::<T>())
+    }
+
+    pub fn with_blocks_per_chunk(layout: Layout, n: usize) -> Pool {
+        // Free blocks hold the list link, so they can't be any smaller
+        let align = if layout.align() < align_of::<FreeBlock>() {
+            align_of::<FreeBlock>()
+        } else {
+            layout.align()
+        };
+        let size = if layout.size() < size_of::<FreeBlock>() {
+            size_of::<FreeBlock>()
+        } else {
+            layout.size()
+        };
+
+        Pool {
+            block_size: round_up(size, align),
+            block_align: align,
+            blocks_per_chunk: if n == 0 { 1 } else { n },
+            free: Cell::new(null_mut()),
+            chunks: Cell::new(null_mut())
+        }
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    fn header_size(&self) -> usize {
+        round_up(size_of::<PoolChunk>(), self.block_align)
+    }
+
+    fn chunk_layout(&self) -> Layout {
+        let size = self.block_size
+            .checked_mul(self.blocks_per_chunk)
+            .and_then(|blocks| blocks.checked_add(self.header_size()));
+        match size.map(|size| Layout::from_size_align(size, self.block_align)) {
+            Some(Ok(layout)) => layout,
+            _ => capacity_overflow()
+        }
+    }
+
+    /// Allocates another chunk and threads its blocks onto the free list
+    fn refill(&self) -> bool {
+        let chunk = match Malloc.allocate(self.chunk_layout()) {
+            Some(ptr) => ptr.as_ptr() as *mut PoolChunk,
+            None => return false
+        };
+
+        unsafe {
+            chunk.write(PoolChunk { next: self.chunks.get() });
+            self.chunks.set(chunk);
+
+            let blocks = (chunk as *mut u8).add(self.header_size());
+            for i in 0..self.blocks_per_chunk {
+                let block = blocks.add(i * self.block_size) as *mut FreeBlock;
+                block.write(FreeBlock { next: self.free.get() });
+                self.free.set(block);
+            }
+        }
+
+        true
+    }
+}
+
+unsafe impl Allocator for Pool {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        if layout.size() > self.block_size { return None; }
+        if layout.align() > self.block_align { return None; }
+
+        if self.free.get().is_null() && !self.refill() {
+            return None;
+        }
+
+        let block = self.free.get();
+        unsafe { self.free.set((*block).next); }
+        NonNull::new(block as *mut u8)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _: Layout) {
+        let block = ptr.as_ptr() as *mut FreeBlock;
+        block.write(FreeBlock { next: self.free.get() });
+        self.free.set(block);
+    }
+
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize
+    ) -> Option<NonNull<u8>> {
+        if new_size <= self.block_size && layout.align() <= self.block_align {
+            Some(ptr)
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        let layout = self.chunk_layout();
+        let mut chunk = self.chunks.get();
+        while !chunk.is_null() {
+            unsafe {
+                let next = (*chunk).next;
+                Malloc.deallocate(NonNull::new_unchecked(chunk as *mut u8), layout);
+                chunk = next;
+            }
+        }
+    }
+}
+
+fn round_up(n: usize, align: usize) -> usize {
+    (n + align - 1) & !(align - 1)
+}
@@ -0,0 +1,62 @@
+use crate::alloc::{Allocator, Malloc};
+use core::{
+    alloc::Layout,
+    ops::Drop,
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering}
+};
+
+/// What the `Counting` handles pointing at it have seen. Atomic so that a
+/// `static` one can be shared with other threads
+pub(crate) struct Counts {
+    allocations: AtomicUsize,
+    deallocations: AtomicUsize,
+    drops: AtomicUsize
+}
+
+impl Counts {
+    pub const fn new() -> Counts {
+        Counts {
+            allocations: AtomicUsize::new(0),
+            deallocations: AtomicUsize::new(0),
+            drops: AtomicUsize::new(0)
+        }
+    }
+
+    pub fn allocations(&self) -> usize {
+        self.allocations.load(Ordering::SeqCst)
+    }
+
+    pub fn deallocations(&self) -> usize {
+        self.deallocations.load(Ordering::SeqCst)
+    }
+
+    /// How many `Counting` handles have been dropped, clones included
+    pub fn drops(&self) -> usize {
+        self.drops.load(Ordering::SeqCst)
+    }
+}
+
+/// Passes everything on to `Malloc` and records it in `Counts`, so tests
+/// can check that containers free memory, and drop their allocator,
+/// exactly as often as they should
+#[derive(Clone)]
+pub(crate) struct Counting<'a>(pub &'a Counts);
+
+unsafe impl Allocator for Counting<'_> {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        self.0.allocations.fetch_add(1, Ordering::SeqCst);
+        Malloc.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.0.deallocations.fetch_add(1, Ordering::SeqCst);
+        Malloc.deallocate(ptr, layout)
+    }
+}
+
+impl Drop for Counting<'_> {
+    fn drop(&mut self) {
+        self.0.drops.fetch_add(1, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,340 @@
+use crate::{
+    alloc::spin::SpinLock,
+    io::{stderr, Write},
+    stdlib::{atexit, calloc, free, posix_memalign}
+};
+use core::{
+    alloc::Layout,
+    fmt::Result as FmtResult,
+    mem::{size_of, MaybeUninit},
+    panic::Location,
+    ptr::null_mut
+};
+
+const INITIAL_CAPACITY: usize = 1024;
+const RECENTLY_FREED: usize = 64;
+
+const EMPTY: usize = 0;
+const TOMBSTONE: usize = 1;
+
+type Site = &'static Location<'static>;
+
+#[derive(Clone, Copy)]
+struct Entry {
+    ptr: usize,
+    size: usize,
+    align: usize,
+    site: Option<Site>
+}
+
+const EMPTY_ENTRY: Entry = Entry { ptr: EMPTY, size: 0, align: 0, site: None };
+
+#[derive(Clone, Copy)]
+struct Freed {
+    entry: Entry,
+    site: Option<Site>
+}
+
+/// Open-addressed table of live allocations. Its storage comes straight
+/// from the C heap so that bookkeeping never shows up in the report
+struct Table {
+    entries: *mut Entry,
+    capacity: usize,
+    live: usize,
+    used: usize,
+    freed: [Freed; RECENTLY_FREED],
+    freed_next: usize,
+    registered: bool
+}
+
+unsafe impl Send for Table {}
+
+static TABLE: SpinLock<Table> = SpinLock::new(Table {
+    entries: null_mut(),
+    capacity: 0,
+    live: 0,
+    used: 0,
+    freed: [Freed { entry: EMPTY_ENTRY, site: None }; RECENTLY_FREED],
+    freed_next: 0,
+    registered: false
+});
+
+fn hash(ptr: usize) -> usize {
+    (ptr >> 4).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+}
+
+impl Table {
+    fn insert(&mut self, entry: Entry) {
+        if (self.used + 1) * 10 > self.capacity * 7 {
+            self.rehash();
+        }
+
+        let mask = self.capacity - 1;
+        let mut i = hash(entry.ptr) & mask;
+        unsafe {
+            loop {
+                let slot = &mut *self.entries.add(i);
+                if slot.ptr == EMPTY || slot.ptr == TOMBSTONE {
+                    if slot.ptr == EMPTY { self.used += 1; }
+                    *slot = entry;
+                    self.live += 1;
+                    return;
+                }
+                i = (i + 1) & mask;
+            }
+        }
+    }
+
+    fn remove(&mut self, ptr: usize) -> Option<Entry> {
+        if self.capacity == 0 { return None; }
+
+        let mask = self.capacity - 1;
+        let mut i = hash(ptr) & mask;
+        unsafe {
+            loop {
+                let slot = &mut *self.entries.add(i);
+                if slot.ptr == EMPTY { return None; }
+                if slot.ptr == ptr {
+                    let entry = *slot;
+                    slot.ptr = TOMBSTONE;
+                    self.live -= 1;
+                    return Some(entry);
+                }
+                i = (i + 1) & mask;
+            }
+        }
+    }
+
+    /// Grows the table when it is mostly live entries, otherwise just
+    /// clears out tombstones
+    fn rehash(&mut self) {
+        let capacity = if self.capacity == 0 {
+            INITIAL_CAPACITY
+        } else if self.live * 2 > self.capacity {
+            self.capacity * 2
+        } else {
+            self.capacity
+        };
+
+        let entries = unsafe {
+            let mut ptr: MaybeUninit<*mut Entry> = MaybeUninit::uninit();
+            let rc = posix_memalign(
+                ptr.as_mut_ptr() as *mut *mut _,
+                16,
+                capacity * size_of::<Entry>()
+            );
+            if rc != 0 { crate::abort(); }
+            let ptr = ptr.assume_init();
+            for i in 0..capacity {
+                ptr.add(i).write(EMPTY_ENTRY);
+            }
+            ptr
+        };
+
+        let old_entries = self.entries;
+        let old_capacity = self.capacity;
+        self.entries = entries;
+        self.capacity = capacity;
+        self.live = 0;
+        self.used = 0;
+
+        for i in 0..old_capacity {
+            let entry = unsafe { *old_entries.add(i) };
+            if entry.ptr != EMPTY && entry.ptr != TOMBSTONE {
+                self.insert(entry);
+            }
+        }
+        if old_entries != null_mut() {
+            unsafe { free(old_entries as *mut _); }
+        }
+    }
+
+    fn find_freed(&self, ptr: usize) -> Option<Freed> {
+        self.freed.iter().find(|f| f.entry.ptr == ptr).copied()
+    }
+
+    fn remember_freed(&mut self, entry: Entry, site: Option<Site>) {
+        self.freed[self.freed_next] = Freed { entry: entry, site: site };
+        self.freed_next = (self.freed_next + 1) % RECENTLY_FREED;
+    }
+
+    fn forget_freed(&mut self, ptr: usize) {
+        for f in self.freed.iter_mut() {
+            if f.entry.ptr == ptr {
+                f.entry.ptr = EMPTY;
+            }
+        }
+    }
+}
+
+struct SiteDisplay(Option<Site>);
+
+impl core::fmt::Display for SiteDisplay {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> FmtResult {
+        match self.0 {
+            Some(site) => write!(f, "{}:{}:{}", site.file(), site.line(), site.column()),
+            None => write!(f, "<unknown>")
+        }
+    }
+}
+
+extern "C" fn report_at_exit() {
+    report_leaks();
+}
+
+pub(crate) fn on_alloc(ptr: *mut u8, layout: Layout, site: Site) {
+    if ptr == null_mut() { return; }
+
+    TABLE.with(|table| {
+        if !table.registered {
+            table.registered = true;
+            unsafe { atexit(report_at_exit); }
+        }
+
+        table.forget_freed(ptr as usize);
+        table.insert(Entry {
+            ptr: ptr as usize,
+            size: layout.size(),
+            align: layout.align(),
+            site: Some(site)
+        });
+    })
+}
+
+/// Something wrong with a free. It is copied out of the table so that it
+/// can be printed after the table is unlocked: a thread holding the stderr
+/// lock may be waiting on the table to free something
+enum Problem {
+    DoubleFree(Freed),
+    Untracked,
+    Mismatch(Entry, Layout)
+}
+
+/// Records a free and checks it against the recorded allocation. Returns
+/// false for double frees, which must not reach the C heap
+pub(crate) fn on_dealloc(ptr: *mut u8, layout: Option<Layout>, site: Site) -> bool {
+    if ptr == null_mut() { return true; }
+
+    let problem = TABLE.with(|table| {
+        let entry = match table.remove(ptr as usize) {
+            Some(entry) => entry,
+            None => return Some(match table.find_freed(ptr as usize) {
+                Some(freed) => Problem::DoubleFree(freed),
+                None => Problem::Untracked
+            })
+        };
+        table.remember_freed(entry, Some(site));
+
+        match layout {
+            Some(layout) if layout.size() != entry.size || layout.align() != entry.align =>
+                Some(Problem::Mismatch(entry, layout)),
+            _ => None
+        }
+    });
+
+    match problem {
+        None => true,
+        Some(Problem::DoubleFree(freed)) => {
+            let _ = writeln!(
+                stderr().lock(),
+                "double free of {:p} ({} bytes) at {}: allocated at {}, first freed at {}",
+                ptr,
+                freed.entry.size,
+                site,
+                SiteDisplay(freed.entry.site),
+                SiteDisplay(freed.site)
+            );
+            false
+        }
+        Some(Problem::Untracked) => {
+            let _ = writeln!(
+                stderr().lock(),
+                "free of untracked pointer {:p} at {}",
+                ptr,
+                site
+            );
+            true
+        }
+        Some(Problem::Mismatch(entry, layout)) => {
+            let _ = writeln!(
+                stderr().lock(),
+                "mismatched free of {:p} at {}: allocated as {} bytes (align {}) at {}, freed as {} bytes (align {})",
+                ptr,
+                site,
+                entry.size,
+                entry.align,
+                SiteDisplay(entry.site),
+                layout.size(),
+                layout.align()
+            );
+            true
+        }
+    }
+}
+
+pub(crate) fn on_realloc(
+    ptr: *mut u8,
+    layout: Layout,
+    new_ptr: *mut u8,
+    new_size: usize,
+    site: Site
+) {
+    if new_ptr == null_mut() { return; }
+
+    on_dealloc(ptr, Some(layout), site);
+    let new_layout = unsafe {
+        Layout::from_size_align_unchecked(new_size, layout.align())
+    };
+    on_alloc(new_ptr, new_layout, site);
+}
+
+/// Prints every allocation that is still live along with where it was made
+/// and returns how many there were. This runs automatically at exit
+pub fn report_leaks() -> usize {
+    // Copy the live entries out, into memory the tracker doesn't see, so
+    // the table isn't locked while printing
+    let (leaks, count) = TABLE.with(|table| {
+        if table.live == 0 {
+            return (null_mut(), 0);
+        }
+
+        let leaks = unsafe { calloc(table.live, size_of::<Entry>()) } as *mut Entry;
+        if leaks == null_mut() { crate::abort(); }
+
+        let mut count = 0;
+        for i in 0..table.capacity {
+            let entry = unsafe { *table.entries.add(i) };
+            if entry.ptr == EMPTY || entry.ptr == TOMBSTONE { continue; }
+
+            unsafe { leaks.add(count).write(entry); }
+            count += 1;
+        }
+        (leaks, count)
+    });
+
+    let mut report = stderr().lock();
+    let mut bytes = 0;
+    for i in 0..count {
+        let entry = unsafe { *leaks.add(i) };
+        bytes += entry.size;
+        let _ = writeln!(
+            report,
+            "leak: {} bytes (align {}) at {:#x} allocated at {}",
+            entry.size,
+            entry.align,
+            entry.ptr,
+            SiteDisplay(entry.site)
+        );
+    }
+
+    if count > 0 {
+        let _ = writeln!(
+            report,
+            "{} allocations ({} bytes) leaked",
+            count,
+            bytes
+        );
+        unsafe { free(leaks as *mut _); }
+    }
+    count
+}
@@ -0,0 +1,98 @@
+use crate::alloc::{Allocator, Malloc};
+use core::{
+    alloc::Layout,
+    cell::Cell,
+    ptr::NonNull
+};
+
+/// Wraps another allocator and keeps count of what goes through it. Useful
+/// for checking how much memory a subsystem holds
+pub struct Tracking<A: Allocator = Malloc> {
+    inner: A,
+    live_allocations: Cell<usize>,
+    live_bytes: Cell<usize>,
+    peak_bytes: Cell<usize>,
+    total_allocations: Cell<usize>
+}
+
+impl Tracking {
+    pub fn new() -> Tracking {
+        Tracking::wrap(Malloc)
+    }
+}
+
+impl<A: Allocator> Tracking<A> {
+    pub fn wrap(inner: A) -> Tracking<A> {
+        Tracking {
+            inner: inner,
+            live_allocations: Cell::new(0),
+            live_bytes: Cell::new(0),
+            peak_bytes: Cell::new(0),
+            total_allocations: Cell::new(0)
+        }
+    }
+
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    pub fn live_allocations(&self) -> usize {
+        self.live_allocations.get()
+    }
+
+    pub fn live_bytes(&self) -> usize {
+        self.live_bytes.get()
+    }
+
+    pub fn peak_bytes(&self) -> usize {
+        self.peak_bytes.get()
+    }
+
+    pub fn total_allocations(&self) -> usize {
+        self.total_allocations.get()
+    }
+
+    fn add_bytes(&self, n: usize) {
+        let live = self.live_bytes.get() + n;
+        self.live_bytes.set(live);
+        if live > self.peak_bytes.get() {
+            self.peak_bytes.set(live);
+        }
+    }
+}
+
+impl Default for Tracking {
+    fn default() -> Tracking {
+        Tracking::new()
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for Tracking<A> {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let ptr = self.inner.allocate(layout)?;
+        self.live_allocations.set(self.live_allocations.get() + 1);
+        self.total_allocations.set(self.total_allocations.get() + 1);
+        self.add_bytes(layout.size());
+
+        Some(ptr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.inner.deallocate(ptr, layout);
+        self.live_allocations.set(self.live_allocations.get() - 1);
+        self.live_bytes.set(self.live_bytes.get() - layout.size());
+    }
+
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize
+    ) -> Option<NonNull<u8>> {
+        let new_ptr = self.inner.reallocate(ptr, layout, new_size)?;
+        self.live_bytes.set(self.live_bytes.get() - layout.size());
+        self.add_bytes(new_size);
+
+        Some(new_ptr)
+    }
+}
@@ -0,0 +1,231 @@
+use crate::ffi::CStr;
+use c::{
+    types::c_void,
+    unwind::{
+        _Unwind_Backtrace, _Unwind_Context, _Unwind_GetIP, _Unwind_Reason_Code,
+        _URC_END_OF_STACK, _URC_NO_REASON
+    }
+};
+use core::{
+    fmt::{Debug, Display, Formatter, Result as FmtResult, Write},
+    mem::transmute,
+    str::from_utf8,
+    sync::atomic::{AtomicUsize, Ordering}
+};
+
+/// Frames past this many are left off
+pub const MAX_FRAMES: usize = 64;
+
+/// Return addresses up the stack, innermost first. Capturing doesn't
+/// allocate, so it is safe from a signal handler or a failing allocator
+#[derive(Clone)]
+pub struct Backtrace {
+    frames: [usize; MAX_FRAMES],
+    len: usize
+}
+
+/// What a resolver knows about an address. The names borrow from the
+/// object's own tables, which go away if it is unloaded
+#[derive(Clone, Copy, Debug)]
+pub struct Symbol<'a> {
+    /// The mangled name of the function the address falls in
+    pub name: Option<&'a CStr>,
+    /// Where that function starts
+    pub address: usize,
+    /// The executable or shared library the address falls in
+    pub object: Option<&'a CStr>,
+    /// Where that object is loaded
+    pub object_base: usize
+}
+
+/// Looks up the symbol an address falls in and, if there is one, passes
+/// it to the callback. It is only lent for the call, so nothing can hold
+/// on to names that unloading a library would leave dangling
+pub type Resolver = fn(usize, &mut dyn FnMut(&Symbol<'_>));
+
+// A `Resolver`, or zero before one is set
+static RESOLVER: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets how `Backtrace` names its frames when displayed. lstd can't read
+/// symbol tables itself, so without one only addresses are shown
+pub fn set_resolver(resolver: Resolver) {
+    RESOLVER.store(resolver as usize, Ordering::Release);
+}
+
+fn resolve(address: usize, found: &mut dyn FnMut(&Symbol<'_>)) {
+    match RESOLVER.load(Ordering::Acquire) {
+        0 => {},
+        resolver => unsafe { transmute::<usize, Resolver>(resolver)(address, found) }
+    }
+}
+
+impl Backtrace {
+    /// Walks the stack from the caller up, using the unwind tables
+    #[inline(never)]
+    pub fn capture() -> Backtrace {
+        extern "C" fn trace(context: *mut _Unwind_Context, arg: *mut c_void) -> _Unwind_Reason_Code {
+            let backtrace = unsafe { &mut *(arg as *mut Backtrace) };
+            if backtrace.len == MAX_FRAMES {
+                return _URC_END_OF_STACK;
+            }
+
+            let ip = unsafe { _Unwind_GetIP(context) };
+            if ip != 0 {
+                backtrace.frames[backtrace.len] = ip;
+                backtrace.len += 1;
+            }
+            _URC_NO_REASON
+        }
+
+        let mut backtrace = Backtrace { frames: [0; MAX_FRAMES], len: 0 };
+        unsafe {
+            _Unwind_Backtrace(trace, &mut backtrace as *mut Backtrace as *mut c_void);
+        }
+
+        // The first frame is this function
+        if backtrace.len > 0 {
+            backtrace.frames.copy_within(1..backtrace.len, 0);
+            backtrace.len -= 1;
+        }
+        backtrace
+    }
+
+    pub fn frames(&self) -> &[usize] {
+        &self.frames[..self.len]
+    }
+}
+
+/// A frame a line, with function names once a resolver is set. Frames
+/// without a name show their offset into the object for `addr2line`
+impl Display for Backtrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for (i, &ip) in self.frames().iter().enumerate() {
+            write!(f, "{:>4}: {:#018x}", i, ip)?;
+
+            // A return address points past the call, possibly into the
+            // next function
+            let mut result = Ok(());
+            resolve(ip - 1, &mut |symbol| result = write_symbol(f, ip, symbol));
+            result?;
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+fn write_symbol(f: &mut Formatter<'_>, ip: usize, symbol: &Symbol<'_>) -> FmtResult {
+    match *symbol {
+        Symbol { name: Some(name), address, object, .. } => {
+            write!(f, " {}+{:#x}", Demangle(name.to_bytes()), ip - address)?;
+            if let Some(object) = object {
+                write!(f, " ({})", object.to_string_lossy())?;
+            }
+        },
+        Symbol { name: None, object: Some(object), object_base, .. } => {
+            write!(f, " ({}+{:#x})", object.to_string_lossy(), ip - object_base)?;
+        },
+        _ => {}
+    }
+    Ok(())
+}
+
+impl Debug for Backtrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_list().entries(self.frames().iter().map(|ip| *ip as *const c_void)).finish()
+    }
+}
+
+/// Writes a Rust symbol the way it appears in source, as in
+/// `lstd::backtrace::Backtrace::capture`, and anything else unchanged
+struct Demangle<'a>(&'a [u8]);
+
+impl Display for Demangle<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let raw = from_utf8(self.0).unwrap_or("<non-UTF-8 symbol>");
+        // LLVM adds `.llvm.<number>` to symbols it had to make unique
+        let mangled = raw.find(".llvm.").map_or(raw, |i| &raw[..i]);
+        let mut rest = match mangled.strip_prefix("_ZN").and_then(|s| s.strip_suffix('E')) {
+            Some(rest) => rest,
+            None => return f.write_str(raw)
+        };
+
+        // Check the whole thing parses before writing any of it
+        let mut parts = 0;
+        let mut check = rest;
+        while !check.is_empty() {
+            match split_part(check) {
+                Some((_, next)) => check = next,
+                None => return f.write_str(raw)
+            }
+            parts += 1;
+        }
+
+        let mut i = 0;
+        while let Some((part, next)) = split_part(rest) {
+            rest = next;
+            i += 1;
+            // The last part is a hash telling instances apart
+            if i == parts && part.len() == 17 && part.starts_with('h') {
+                break;
+            }
+            if i > 1 {
+                f.write_str("::")?;
+            }
+            write_part(f, part)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits a length-prefixed identifier off the front of `s`
+fn split_part(s: &str) -> Option<(&str, &str)> {
+    let digits = s.bytes().take_while(u8::is_ascii_digit).count();
+    let len: usize = s[..digits].parse().ok()?;
+    let rest = &s[digits..];
+    if len > rest.len() || !rest.is_char_boundary(len) {
+        return None;
+    }
+    Some((&rest[..len], &rest[len..]))
+}
+
+/// Undoes the escapes legacy mangling uses for characters symbols can't
+/// hold
+fn write_part(f: &mut Formatter<'_>, part: &str) -> FmtResult {
+    // Parts can't start with `$`, so an escape there gets a `_` first
+    let mut rest = if part.starts_with("_$") { &part[1..] } else { part };
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("..") {
+            f.write_str("::")?;
+            rest = after;
+        } else if rest.starts_with('$') {
+            let end = match rest[1..].find('$') {
+                Some(end) => end + 1,
+                None => return f.write_str(rest)
+            };
+            let escape = &rest[1..end];
+            let c = match escape {
+                "SP" => '@',
+                "BP" => '*',
+                "RF" => '&',
+                "LT" => '<',
+                "GT" => '>',
+                "LP" => '(',
+                "RP" => ')',
+                "C" => ',',
+                _ => match escape.strip_prefix('u').and_then(|hex| u32::from_str_radix(hex, 16).ok()) {
+                    Some(code) => core::char::from_u32(code).unwrap_or('?'),
+                    None => return f.write_str(rest)
+                }
+            };
+            f.write_char(c)?;
+            rest = &rest[end + 1..];
+        } else {
+            let end = rest.find(['$', '.']).unwrap_or(rest.len());
+            // A lone dot is kept as it is
+            let end = if end == 0 { 1 } else { end };
+            f.write_str(&rest[..end])?;
+            rest = &rest[end..];
+        }
+    }
+    Ok(())
+}
@@ -1,103 +1,545 @@
+mod deque;
 mod rc;
 
+pub use deque::*;
 pub use rc::*;
 
-use crate::alloc::{alloc, dealloc};
+use crate::alloc::{capacity_overflow, handle_alloc_error, Allocator, Box, Malloc};
 use core::{
-    cell::Cell,
-    mem::transmute_copy,
-    ops::{Deref, DerefMut, Drop},
-    ptr::drop_in_place,
-    slice::{from_raw_parts, from_raw_parts_mut}
+    alloc::Layout,
+    cmp::max,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    iter::{FromIterator, FusedIterator},
+    marker::PhantomData,
+    mem::{align_of, forget, size_of},
+    ops::{Bound, Deref, DerefMut, Drop, RangeBounds},
+    ptr::{copy, copy_nonoverlapping, drop_in_place, read, slice_from_raw_parts_mut, NonNull},
+    slice::{from_raw_parts, from_raw_parts_mut, Iter, IterMut}
 };
 
-pub struct Array<T> {
+const MIN_CAPACITY: usize = 4;
+
+pub struct Array<T, A: Allocator = Malloc> {
     data: *mut T,
-    len: Cell<isize>,
-    cap: usize
+    len: usize,
+    cap: usize,
+    alloc: A
 }
 
+unsafe impl<T: Send, A: Allocator + Send> Send for Array<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for Array<T, A> {}
+
 impl<T> Array<T> {
-    pub fn new(cap: usize) -> Array<T> {
-        let data: *mut T = alloc(cap).unwrap();
-        let len = Cell::new(0);
+    /// Creates an empty array. Nothing is allocated until the first push
+    pub const fn new() -> Array<T> {
+        Array::new_in(Malloc)
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn with_capacity(cap: usize) -> Array<T> {
+        Array::with_capacity_in(cap, Malloc)
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn from_slice(array: &[T]) -> Array<T> where T: Clone {
+        Array::from_slice_in(array, Malloc)
+    }
+}
+
+impl<T, A: Allocator> Array<T, A> {
+    pub const fn new_in(alloc: A) -> Array<T, A> {
+        let cap = if size_of::<T>() == 0 { usize::MAX } else { 0 };
         Array {
-            data: data,
-            len: len,
-            cap: cap
+            data: NonNull::dangling().as_ptr(),
+            len: 0,
+            cap: cap,
+            alloc: alloc
         }
     }
 
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Array<T, A> {
+        let mut array = Array::new_in(alloc);
+        array.reserve(cap);
+        array
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn from_slice_in(array: &[T], alloc: A) -> Array<T, A> where T: Clone {
+        let mut result = Array::with_capacity_in(array.len(), alloc);
+        result.extend_from_slice(array);
+        result
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
     /// This allows one to initialize an array from foreign code
-    pub unsafe fn assume_init(&self, len: isize) {
-        self.len.set(len);
+    ///
+    /// # Safety
+    ///
+    /// The first `len` elements must have been written and `len` must not
+    /// exceed the capacity
+    pub unsafe fn set_len(&mut self, len: usize) {
+        self.len = len;
     }
 
-    pub fn push(&mut self, value: T) -> bool {
-        if self.len.get() == self.cap as isize { return false; }
+    pub fn len(&self) -> usize {
+        self.len
+    }
 
-        unsafe { self.data.offset(self.len.get()).write(value); }
-        self.len.set(self.len.get() + 1);
-        true
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
-    pub fn pop(&mut self) -> Option<T> {
-        if self.len.get() > 0 {
-            self.len.set(self.len.get() - 1);
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// Makes room for at least `additional` more elements, growing the
+    /// allocation geometrically
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn reserve(&mut self, additional: usize) {
+        let required = match self.len.checked_add(additional) {
+            Some(required) => required,
+            None => capacity_overflow()
+        };
+        if required <= self.cap { return; }
+
+        let doubled = self.cap.saturating_mul(2);
+        self.grow(max(required, max(doubled, MIN_CAPACITY)));
+    }
+
+    /// Gives back any capacity beyond the current length
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn shrink_to_fit(&mut self) {
+        if size_of::<T>() == 0 || self.cap == self.len { return; }
+
+        if self.len == 0 {
+            unsafe { free_buffer(&self.alloc, self.data, self.cap); }
+            self.data = NonNull::dangling().as_ptr();
+            self.cap = 0;
+            return;
+        }
+
+        let data = unsafe {
+            self.alloc.reallocate(
+                NonNull::new_unchecked(self.data as *mut u8),
+                array_layout::<T>(self.cap),
+                self.len * size_of::<T>()
+            )
+        };
+        self.data = match data {
+            Some(data) => data.as_ptr() as *mut T,
+            None => handle_alloc_error(array_layout::<T>(self.len))
+        };
+        self.cap = self.len;
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn into_boxed_slice(mut self) -> Box<[T], A> {
+        self.shrink_to_fit();
+        let slice = slice_from_raw_parts_mut(self.data, self.len);
+        let alloc = unsafe { read(&self.alloc) };
+        forget(self);
+        unsafe { Box::from_raw_in(slice, alloc) }
+    }
+
+    pub fn from_boxed_slice(slice: Box<[T], A>) -> Array<T, A> {
+        let (data, alloc) = Box::into_raw_with_allocator(slice);
+        let len = data.len();
+        let cap = if size_of::<T>() == 0 { usize::MAX } else { len };
+        Array {
+            data: data as *mut T,
+            len: len,
+            cap: cap,
+            alloc: alloc
+        }
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn grow(&mut self, cap: usize) {
+        let layout = match Layout::array::<T>(cap) {
+            Ok(layout) => layout,
+            Err(_) => capacity_overflow()
+        };
+        let data = if self.cap == 0 {
+            self.alloc.allocate(layout)
+        } else {
             unsafe {
-                // The transmute_copy() is used to get around the fact
-                // that T is not a Copy type
-                let result = &*self.data.offset(self.len.get());
-                Some(transmute_copy(result))
+                self.alloc.reallocate(
+                    NonNull::new_unchecked(self.data as *mut u8),
+                    array_layout::<T>(self.cap),
+                    layout.size()
+                )
             }
+        };
+
+        self.data = match data {
+            Some(data) => data.as_ptr() as *mut T,
+            None => handle_alloc_error(layout)
+        };
+        self.cap = cap;
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn push(&mut self, value: T) {
+        if self.len == self.cap { self.reserve(1); }
+
+        unsafe { self.data.add(self.len).write(value); }
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len > 0 {
+            self.len -= 1;
+            unsafe { Some(self.data.add(self.len).read()) }
         } else {
             None
         }
     }
 
-    pub fn from_slice(array: &[T]) -> Array<T> {
-        let data: *mut T = alloc(array.len()).unwrap();
-        for (i, x) in array.iter().enumerate() {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len);
+        if self.len == self.cap { self.reserve(1); }
+
+        unsafe {
+            let p = self.data.add(index);
+            copy(p, p.add(1), self.len - index);
+            p.write(value);
+        }
+        self.len += 1;
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len);
+
+        unsafe {
+            let p = self.data.add(index);
+            let result = p.read();
+            copy(p.add(1), p, self.len - index - 1);
+            self.len -= 1;
+            result
+        }
+    }
+
+    /// Removes an element by moving the last element into its place. This
+    /// does not preserve ordering but is O(1)
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        assert!(index < self.len);
+
+        unsafe {
+            let p = self.data.add(index);
+            let result = p.read();
+            self.len -= 1;
+            if index != self.len {
+                copy_nonoverlapping(self.data.add(self.len), p, 1);
+            }
+            result
+        }
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len { return; }
+
+        let old_len = self.len;
+        self.len = len;
+        unsafe {
+            let tail = from_raw_parts_mut(self.data.add(len), old_len - len);
+            drop_in_place(tail);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn extend_from_slice(&mut self, other: &[T]) where T: Clone {
+        self.reserve(other.len());
+        for x in other.iter() {
+            self.push(x.clone());
+        }
+    }
+
+    /// Keeps only the elements for which `f` returns true, preserving their
+    /// order
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let len = self.len;
+        let mut deleted = 0;
+
+        // Nothing is visible through self while elements are being shuffled
+        self.len = 0;
+        for i in 0..len {
             unsafe {
-                data
-                    .offset(i as isize)
-                    .write(transmute_copy(&*x));
+                let p = self.data.add(i);
+                if !f(&*p) {
+                    drop_in_place(p);
+                    deleted += 1;
+                } else if deleted > 0 {
+                    copy_nonoverlapping(p, p.sub(deleted), 1);
+                }
             }
         }
+        self.len = len - deleted;
+    }
 
-        Array {
-            data: data,
-            len: Cell::new(array.len() as isize),
-            cap: array.len()
+    /// Removes the elements in `range` and yields them by value. The
+    /// remaining elements are shifted down when the iterator is dropped
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, A> {
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n + 1,
+            Bound::Unbounded => 0
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n + 1,
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => self.len
+        };
+        assert!(start <= end && end <= self.len);
+
+        let tail_len = self.len - end;
+        self.len = start;
+        Drain {
+            array: self as *mut _,
+            start: start,
+            end: end,
+            tail: end,
+            tail_len: tail_len,
+            _marker: PhantomData
         }
     }
+}
 
-    pub fn len(&self) -> isize {
-        self.len.get()
+/// Layout of a buffer that is known to have been allocated successfully
+fn array_layout<T>(cap: usize) -> Layout {
+    unsafe {
+        Layout::from_size_align_unchecked(cap * size_of::<T>(), align_of::<T>())
     }
 }
 
-impl<T> Drop for Array<T> {
-    fn drop(&mut self) {
-        for x in 0..self.len.get() {
-            unsafe { drop_in_place(self.data.offset(x as isize)); }
-        }
+unsafe fn free_buffer<T, A: Allocator>(alloc: &A, data: *mut T, cap: usize) {
+    if cap != 0 && size_of::<T>() != 0 {
+        alloc.deallocate(
+            NonNull::new_unchecked(data as *mut u8),
+            array_layout::<T>(cap)
+        );
+    }
+}
 
-        dealloc(self.data);
+impl<T, A: Allocator> Drop for Array<T, A> {
+    fn drop(&mut self) {
+        self.clear();
+        unsafe { free_buffer(&self.alloc, self.data, self.cap); }
     }
 }
 
-impl<T> Deref for Array<T> {
+impl<T, A: Allocator> Deref for Array<T, A> {
     type Target = [T];
 
     fn deref(&self) -> &Self::Target {
-        unsafe { from_raw_parts(self.data, self.len.get() as usize) }
+        unsafe { from_raw_parts(self.data, self.len) }
     }
 }
 
-impl<T> DerefMut for Array<T> {
+impl<T, A: Allocator> DerefMut for Array<T, A> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { from_raw_parts_mut(self.data, self.len.get() as usize) }
+        unsafe { from_raw_parts_mut(self.data, self.len) }
     }
 }
 
+impl<T> Default for Array<T> {
+    fn default() -> Array<T> {
+        Array::new()
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for Array<T, A> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn clone(&self) -> Array<T, A> {
+        Array::from_slice_in(self, self.alloc.clone())
+    }
+}
+
+impl<T: Debug, A: Allocator> Debug for Array<T, A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq, A: Allocator, B: Allocator> PartialEq<Array<T, B>> for Array<T, A> {
+    fn eq(&self, other: &Array<T, B>) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq, A: Allocator> Eq for Array<T, A> {}
+
+impl<T> FromIterator<T> for Array<T> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Array<T> {
+        let mut array = Array::new();
+        array.extend(iter);
+        array
+    }
+}
+
+impl<T, A: Allocator> Extend<T> for Array<T, A> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+impl<T, A: Allocator> IntoIterator for Array<T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+
+    fn into_iter(self) -> IntoIter<T, A> {
+        let iter = IntoIter {
+            data: self.data,
+            cap: self.cap,
+            start: 0,
+            end: self.len,
+            alloc: unsafe { read(&self.alloc) }
+        };
+        forget(self);
+        iter
+    }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a Array<T, A> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a mut Array<T, A> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+pub struct IntoIter<T, A: Allocator = Malloc> {
+    data: *mut T,
+    cap: usize,
+    start: usize,
+    end: usize,
+    alloc: A
+}
+
+unsafe impl<T: Send, A: Allocator + Send> Send for IntoIter<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for IntoIter<T, A> {}
+
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end { return None; }
+
+        self.start += 1;
+        unsafe { Some(self.data.add(self.start - 1).read()) }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.end - self.start;
+        (n, Some(n))
+    }
+}
+
+impl<T, A: Allocator> DoubleEndedIterator for IntoIter<T, A> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end { return None; }
+
+        self.end -= 1;
+        unsafe { Some(self.data.add(self.end).read()) }
+    }
+}
+
+impl<T, A: Allocator> ExactSizeIterator for IntoIter<T, A> {}
+impl<T, A: Allocator> FusedIterator for IntoIter<T, A> {}
+
+impl<T, A: Allocator> Drop for IntoIter<T, A> {
+    fn drop(&mut self) {
+        for _ in &mut *self {}
+        unsafe { free_buffer(&self.alloc, self.data, self.cap); }
+    }
+}
+
+pub struct Drain<'a, T, A: Allocator = Malloc> {
+    array: *mut Array<T, A>,
+    start: usize,
+    end: usize,
+    tail: usize,
+    tail_len: usize,
+    _marker: PhantomData<&'a mut Array<T, A>>
+}
+
+impl<'a, T, A: Allocator> Iterator for Drain<'a, T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end { return None; }
+
+        self.start += 1;
+        unsafe { Some((*self.array).data.add(self.start - 1).read()) }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.end - self.start;
+        (n, Some(n))
+    }
+}
+
+impl<'a, T, A: Allocator> DoubleEndedIterator for Drain<'a, T, A> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end { return None; }
+
+        self.end -= 1;
+        unsafe { Some((*self.array).data.add(self.end).read()) }
+    }
+}
+
+impl<'a, T, A: Allocator> ExactSizeIterator for Drain<'a, T, A> {}
+impl<'a, T, A: Allocator> FusedIterator for Drain<'a, T, A> {}
+
+impl<'a, T, A: Allocator> Drop for Drain<'a, T, A> {
+    fn drop(&mut self) {
+        for _ in &mut *self {}
+
+        // Close the gap left by the drained range
+        unsafe {
+            let array = &mut *self.array;
+            let len = array.len;
+            if self.tail != len {
+                copy(array.data.add(self.tail), array.data.add(len), self.tail_len);
+            }
+            array.len = len + self.tail_len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn reserve_past_layout_limit_panics() {
+        let mut array = Array::<u64>::new();
+        array.reserve(usize::MAX / 4);
+    }
+}
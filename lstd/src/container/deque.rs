@@ -0,0 +1,274 @@
+use crate::alloc::{capacity_overflow, handle_alloc_error, Allocator, Malloc};
+use super::{free_buffer, MIN_CAPACITY};
+use core::{
+    alloc::Layout,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    iter::FusedIterator,
+    mem::size_of,
+    ops::Drop,
+    ptr::{copy_nonoverlapping, NonNull}
+};
+
+/// A double-ended queue on a growable ring buffer. The capacity is always a
+/// power of two so that wrapping an index is a mask
+pub struct Deque<T, A: Allocator = Malloc> {
+    data: *mut T,
+    head: usize,
+    len: usize,
+    cap: usize,
+    alloc: A
+}
+
+unsafe impl<T: Send, A: Allocator + Send> Send for Deque<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for Deque<T, A> {}
+
+impl<T> Deque<T> {
+    pub fn new() -> Deque<T> {
+        Deque::new_in(Malloc)
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn with_capacity(cap: usize) -> Deque<T> {
+        Deque::with_capacity_in(cap, Malloc)
+    }
+}
+
+impl<T, A: Allocator> Deque<T, A> {
+    pub fn new_in(alloc: A) -> Deque<T, A> {
+        let cap = if size_of::<T>() == 0 { usize::MAX } else { 0 };
+        Deque {
+            data: NonNull::dangling().as_ptr(),
+            head: 0,
+            len: 0,
+            cap: cap,
+            alloc: alloc
+        }
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Deque<T, A> {
+        let mut deque = Deque::new_in(alloc);
+        deque.reserve(cap);
+        deque
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn reserve(&mut self, additional: usize) {
+        let required = match self.len.checked_add(additional) {
+            Some(required) => required,
+            None => capacity_overflow()
+        };
+        if required <= self.cap { return; }
+
+        let cap = match required.max(MIN_CAPACITY).checked_next_power_of_two() {
+            Some(cap) => cap,
+            None => capacity_overflow()
+        };
+        self.grow(cap);
+    }
+
+    /// Moves the contents into a new buffer, unwrapping them so that the
+    /// front ends up at index zero
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn grow(&mut self, cap: usize) {
+        let layout = match Layout::array::<T>(cap) {
+            Ok(layout) => layout,
+            Err(_) => capacity_overflow()
+        };
+        let data = match self.alloc.allocate(layout) {
+            Some(data) => data.as_ptr() as *mut T,
+            None => handle_alloc_error(layout)
+        };
+
+        unsafe {
+            let (front, back) = self.as_slices();
+            copy_nonoverlapping(front.as_ptr(), data, front.len());
+            copy_nonoverlapping(back.as_ptr(), data.add(front.len()), back.len());
+            free_buffer(&self.alloc, self.data, self.cap);
+        }
+
+        self.data = data;
+        self.head = 0;
+        self.cap = cap;
+    }
+
+    fn slot(&self, i: usize) -> *mut T {
+        if size_of::<T>() == 0 {
+            self.data
+        } else {
+            unsafe { self.data.add((self.head + i) & (self.cap - 1)) }
+        }
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn push_back(&mut self, value: T) {
+        if self.len == self.cap { self.reserve(1); }
+
+        unsafe { self.slot(self.len).write(value); }
+        self.len += 1;
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn push_front(&mut self, value: T) {
+        if self.len == self.cap { self.reserve(1); }
+
+        if size_of::<T>() != 0 {
+            self.head = (self.head + self.cap - 1) & (self.cap - 1);
+        }
+        unsafe { self.slot(0).write(value); }
+        self.len += 1;
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 { return None; }
+
+        self.len -= 1;
+        unsafe { Some(self.slot(self.len).read()) }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 { return None; }
+
+        let value = unsafe { self.slot(0).read() };
+        if size_of::<T>() != 0 {
+            self.head = (self.head + 1) & (self.cap - 1);
+        }
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn get(&self, i: usize) -> Option<&T> {
+        if i < self.len {
+            unsafe { Some(&*self.slot(i)) }
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, i: usize) -> Option<&mut T> {
+        if i < self.len {
+            unsafe { Some(&mut *self.slot(i)) }
+        } else {
+            None
+        }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        if self.len == 0 { None } else { self.get(self.len - 1) }
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_back().is_some() {}
+    }
+
+    /// The contents in order, split where the ring buffer wraps around
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        // An empty deque may not have a buffer to wrap indices in yet
+        if self.len == 0 { return (&[], &[]); }
+
+        unsafe {
+            if size_of::<T>() == 0 || self.head + self.len <= self.cap {
+                let front = core::slice::from_raw_parts(self.slot(0), self.len);
+                (front, &[])
+            } else {
+                let front_len = self.cap - self.head;
+                let front = core::slice::from_raw_parts(self.slot(0), front_len);
+                let back = core::slice::from_raw_parts(self.data, self.len - front_len);
+                (front, back)
+            }
+        }
+    }
+
+    pub fn iter(&self) -> DequeIter<'_, T, A> {
+        DequeIter { deque: self, start: 0, end: self.len }
+    }
+}
+
+impl<T, A: Allocator> Drop for Deque<T, A> {
+    fn drop(&mut self) {
+        self.clear();
+        unsafe { free_buffer(&self.alloc, self.data, self.cap); }
+    }
+}
+
+impl<T> Default for Deque<T> {
+    fn default() -> Deque<T> {
+        Deque::new()
+    }
+}
+
+impl<T: Debug, A: Allocator> Debug for Deque<T, A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, A: Allocator> Extend<T> for Deque<T, A> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for x in iter {
+            self.push_back(x);
+        }
+    }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a Deque<T, A> {
+    type Item = &'a T;
+    type IntoIter = DequeIter<'a, T, A>;
+
+    fn into_iter(self) -> DequeIter<'a, T, A> {
+        self.iter()
+    }
+}
+
+pub struct DequeIter<'a, T, A: Allocator = Malloc> {
+    deque: &'a Deque<T, A>,
+    start: usize,
+    end: usize
+}
+
+impl<'a, T, A: Allocator> Iterator for DequeIter<'a, T, A> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.start == self.end { return None; }
+
+        self.start += 1;
+        self.deque.get(self.start - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.end - self.start;
+        (n, Some(n))
+    }
+}
+
+impl<'a, T, A: Allocator> DoubleEndedIterator for DequeIter<'a, T, A> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.start == self.end { return None; }
+
+        self.end -= 1;
+        self.deque.get(self.end)
+    }
+}
+
+impl<'a, T, A: Allocator> ExactSizeIterator for DequeIter<'a, T, A> {}
+impl<'a, T, A: Allocator> FusedIterator for DequeIter<'a, T, A> {}
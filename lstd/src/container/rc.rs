@@ -1,61 +1,278 @@
 use crate::{
-    alloc::{alloc, dealloc, Box}
+    alloc::{handle_alloc_error, Allocator, Malloc}
 };
 use core::{
+    alloc::Layout,
     cell::Cell,
-    mem::ManuallyDrop,
-    ops::{Deref, Drop}
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    mem::{forget, ManuallyDrop},
+    ops::{Deref, Drop},
+    ptr::{drop_in_place, read, NonNull}
 };
 
-pub struct Rc<T> {
-    counter: *mut Cell<usize>,
-    data: ManuallyDrop<Box<T>>
+/// The single allocation behind an `Rc` and its `Weak` handles. All strong
+/// references together hold one weak reference, so the allocation lives
+/// until the value is dropped and the last `Weak` is gone
+struct RcBox<T> {
+    strong: Cell<usize>,
+    weak: Cell<usize>,
+    value: T
+}
+
+/// A reference-counted pointer for values shared within one thread. Use
+/// `Weak` for back-references so that cycles do not keep values alive
+pub struct Rc<T, A: Allocator = Malloc> {
+    ptr: NonNull<RcBox<T>>,
+    // Taken out in `drop`, which may need to move it into a `Weak`
+    alloc: ManuallyDrop<A>
+}
+
+/// A non-owning reference to a value managed by `Rc`. It keeps the
+/// allocation around but not the value, which can be reached again through
+/// `upgrade` while any `Rc` is still alive
+pub struct Weak<T, A: Allocator = Malloc> {
+    ptr: NonNull<RcBox<T>>,
+    alloc: A
 }
 
 impl<T> Rc<T> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
     pub fn new(value: T) -> Rc<T> {
-        let counter: *mut Cell<usize> = alloc(1).unwrap();
-        let data = ManuallyDrop::new(Box::new(value));
+        Rc::new_in(value, Malloc)
+    }
+}
 
-        unsafe { counter.write(Cell::new(1)); }
+impl<T, A: Allocator> Rc<T, A> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn new_in(value: T, alloc: A) -> Rc<T, A> {
+        let layout = Layout::new::<RcBox<T>>();
+        let ptr: NonNull<RcBox<T>> = match alloc.allocate(layout) {
+            Some(ptr) => ptr.cast(),
+            None => handle_alloc_error(layout)
+        };
 
-        Rc { counter: counter, data: data }
+        unsafe {
+            ptr.as_ptr().write(RcBox {
+                strong: Cell::new(1),
+                weak: Cell::new(1),
+                value: value
+            });
+        }
+
+        Rc { ptr: ptr, alloc: ManuallyDrop::new(alloc) }
     }
 
-    pub fn clone(this: &Rc<T>) -> Rc<T> {
-        unsafe {
-            *(*this.counter).get_mut() += 1;
+    /// Returns a mutable reference to the value if no other `Rc` or `Weak`
+    /// points to it
+    pub fn get_mut(this: &mut Rc<T, A>) -> Option<&mut T> {
+        if Rc::strong_count(this) == 1 && Rc::weak_count(this) == 0 {
+            unsafe { Some(&mut (*this.ptr.as_ptr()).value) }
+        } else {
+            None
         }
+    }
+
+    /// Moves the value out if this is the only strong reference. Any `Weak`
+    /// handles left will fail to upgrade from then on
+    pub fn try_unwrap(this: Rc<T, A>) -> Result<T, Rc<T, A>> {
+        if Rc::strong_count(&this) != 1 {
+            return Err(this);
+        }
+
+        unsafe {
+            let value = read(&this.inner().value);
+            this.inner().strong.set(0);
 
-        let data =
-            ManuallyDrop::new(Box::from_raw(Box::as_ptr(&this.data)));
-        Rc {
-            counter: this.counter,
-            data: data,
+            // Hand the weak reference held by the strong ones to a `Weak`,
+            // which frees the allocation if it was the last one
+            let ptr = this.ptr;
+            let alloc = ManuallyDrop::into_inner(read(&this.alloc));
+            forget(this);
+            drop(Weak { ptr: ptr, alloc: alloc });
+
+            Ok(value)
         }
     }
 
-    pub fn as_ptr(this: &Rc<T>) -> *mut T {
-        Box::as_ptr(&this.data)
+    pub fn ptr_eq(this: &Rc<T, A>, other: &Rc<T, A>) -> bool {
+        this.ptr == other.ptr
+    }
+
+    pub fn strong_count(this: &Rc<T, A>) -> usize {
+        this.inner().strong.get()
+    }
+
+    pub fn weak_count(this: &Rc<T, A>) -> usize {
+        this.inner().weak.get() - 1
+    }
+
+    pub fn as_ptr(this: &Rc<T, A>) -> *mut T {
+        unsafe { &mut (*this.ptr.as_ptr()).value as *mut T }
+    }
+
+    fn inner(&self) -> &RcBox<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T, A: Allocator + Clone> Rc<T, A> {
+    pub fn downgrade(this: &Rc<T, A>) -> Weak<T, A> {
+        let inner = this.inner();
+        inner.weak.set(inner.weak.get() + 1);
+
+        Weak { ptr: this.ptr, alloc: A::clone(&this.alloc) }
+    }
+}
+
+impl<T, A: Allocator + Clone> Clone for Rc<T, A> {
+    fn clone(&self) -> Rc<T, A> {
+        let inner = self.inner();
+        inner.strong.set(inner.strong.get() + 1);
+
+        Rc { ptr: self.ptr, alloc: self.alloc.clone() }
     }
 }
 
-impl<T> Deref for Rc<T> {
+impl<T, A: Allocator> Deref for Rc<T, A> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &*self.data
+        &self.inner().value
     }
 }
 
-impl<T> Drop for Rc<T> {
+impl<T, A: Allocator> Drop for Rc<T, A> {
     fn drop(&mut self) {
+        let inner = self.inner();
+        let strong = inner.strong.get() - 1;
+        inner.strong.set(strong);
+
+        // Taken out exactly once, and dropped here unless a `Weak` takes it
+        let alloc = unsafe { ManuallyDrop::take(&mut self.alloc) };
+        if strong != 0 { return; }
+
         unsafe {
-            *(*self.counter).get_mut() -= 1;
-            if (*self.counter).get() == 0 {
-                dealloc(self.counter);
-                ManuallyDrop::drop(&mut self.data);
-            }
+            drop_in_place(&mut (*self.ptr.as_ptr()).value);
+            drop(Weak { ptr: self.ptr, alloc: alloc });
         }
     }
 }
+
+impl<T: Debug, A: Allocator> Debug for Rc<T, A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Display, A: Allocator> Display for Rc<T, A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq, A: Allocator> PartialEq for Rc<T, A> {
+    fn eq(&self, other: &Rc<T, A>) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq, A: Allocator> Eq for Rc<T, A> {}
+
+impl<T, A: Allocator> Weak<T, A> {
+    /// Number of `Rc`s still keeping the value alive
+    pub fn strong_count(&self) -> usize {
+        self.inner().strong.get()
+    }
+
+    pub fn ptr_eq(&self, other: &Weak<T, A>) -> bool {
+        self.ptr == other.ptr
+    }
+
+    fn inner(&self) -> &RcBox<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T, A: Allocator + Clone> Weak<T, A> {
+    /// Returns a new strong reference, or `None` if the value has already
+    /// been dropped
+    pub fn upgrade(&self) -> Option<Rc<T, A>> {
+        let inner = self.inner();
+        if inner.strong.get() == 0 { return None; }
+
+        inner.strong.set(inner.strong.get() + 1);
+        Some(Rc { ptr: self.ptr, alloc: ManuallyDrop::new(self.alloc.clone()) })
+    }
+}
+
+impl<T, A: Allocator + Clone> Clone for Weak<T, A> {
+    fn clone(&self) -> Weak<T, A> {
+        let inner = self.inner();
+        inner.weak.set(inner.weak.get() + 1);
+
+        Weak { ptr: self.ptr, alloc: self.alloc.clone() }
+    }
+}
+
+impl<T, A: Allocator> Drop for Weak<T, A> {
+    fn drop(&mut self) {
+        let inner = self.inner();
+        inner.weak.set(inner.weak.get() - 1);
+        if inner.weak.get() != 0 { return; }
+
+        unsafe {
+            self.alloc.deallocate(self.ptr.cast(), Layout::new::<RcBox<T>>());
+        }
+    }
+}
+
+impl<T, A: Allocator> Debug for Weak<T, A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("(Weak)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alloc::{
+        testing::{Counting, Counts},
+        Pool
+    };
+
+    #[test]
+    fn drop_with_pool() {
+        let rc = Rc::new_in(7u64, Pool::for_type::<RcBox<u64>>());
+        assert_eq!(*rc, 7);
+        drop(rc);
+    }
+
+    #[test]
+    fn allocator_dropped_once() {
+        let counts = Counts::new();
+        drop(Rc::new_in(1u32, Counting(&counts)));
+        assert_eq!(counts.deallocations(), 1);
+        assert_eq!(counts.drops(), 1);
+
+        let counts = Counts::new();
+        let rc = Rc::new_in(2u32, Counting(&counts));
+        assert_eq!(Rc::try_unwrap(rc).ok(), Some(2));
+        assert_eq!(counts.deallocations(), 1);
+        assert_eq!(counts.drops(), 1);
+    }
+
+    #[test]
+    fn weak_keeps_the_allocation() {
+        let counts = Counts::new();
+        let rc = Rc::new_in(3u32, Counting(&counts));
+        let weak = Rc::downgrade(&rc);
+        drop(rc);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(counts.deallocations(), 0);
+
+        drop(weak);
+        assert_eq!(counts.allocations(), 1);
+        assert_eq!(counts.deallocations(), 1);
+        assert_eq!(counts.drops(), 2);
+    }
+}
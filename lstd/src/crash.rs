@@ -0,0 +1,165 @@
+use crate::{
+    backtrace::Backtrace,
+    fs::Path,
+    io,
+    log::Utc,
+    time::{SystemTime, UNIX_EPOCH}
+};
+use c::{
+    fcntl::{open, O_APPEND, O_CLOEXEC, O_CREAT, O_WRONLY},
+    signal::*,
+    stdio::STDERR,
+    types::{c_char, c_int, c_void},
+    unistd::{close, write}
+};
+use core::{
+    cell::UnsafeCell,
+    fmt::{self, Display, Formatter, Result as FmtResult},
+    mem::forget,
+    ptr::null_mut,
+    sync::atomic::{AtomicBool, AtomicPtr, Ordering}
+};
+
+const SIGNALS: [c_int; 5] = [SIGSEGV, SIGBUS, SIGILL, SIGFPE, SIGABRT];
+
+const ALT_STACK_SIZE: usize = 64 * 1024;
+
+/// Stack for the signal handlers, so a stack overflow can still be
+/// reported
+struct AltStack(UnsafeCell<[u8; ALT_STACK_SIZE]>);
+
+unsafe impl Sync for AltStack {}
+
+static ALT_STACK: AltStack = AltStack(UnsafeCell::new([0; ALT_STACK_SIZE]));
+
+// Only the first crash is reported. Aborting after a panic raises SIGABRT,
+// which would otherwise report it again
+static REPORTED: AtomicBool = AtomicBool::new(false);
+
+// Where reports are appended, or null for stderr only
+static CRASH_LOG: AtomicPtr<c_char> = AtomicPtr::new(null_mut());
+
+/// Why the process is going down, when it was a signal
+struct Signal {
+    signal: c_int,
+    address: usize
+}
+
+/// Writes straight to a file descriptor. The stderr buffer's lock may be
+/// held by the code that crashed
+struct Fd(c_int);
+
+impl fmt::Write for Fd {
+    fn write_str(&mut self, s: &str) -> FmtResult {
+        let mut bytes = s.as_bytes();
+        while !bytes.is_empty() {
+            let written = unsafe { write(self.0, bytes.as_ptr() as *const c_void, bytes.len()) };
+            if written <= 0 {
+                return Err(fmt::Error);
+            }
+            bytes = &bytes[written as usize..];
+        }
+        Ok(())
+    }
+}
+
+/// Reports SIGSEGV, SIGBUS, SIGILL, SIGFPE and SIGABRT the same way as
+/// `report`, then lets the signal kill the process as it would have. With
+/// a `crash_log`, reports are appended to it as well as written to stderr.
+///
+/// Only the calling thread gets the alternate stack, so stack overflows on
+/// other threads die without a report
+pub fn install(crash_log: Option<&Path>) -> io::Result<()> {
+    if let Some(path) = crash_log {
+        let path = path.to_c_string()?;
+        // Kept for the life of the process, since a handler may need it
+        // at any point
+        CRASH_LOG.store(path.as_ptr() as *mut c_char, Ordering::Release);
+        forget(path);
+    }
+
+    let stack = stack_t {
+        ss_sp: ALT_STACK.0.get() as *mut c_void,
+        ss_flags: 0,
+        ss_size: ALT_STACK_SIZE
+    };
+    if unsafe { sigaltstack(&stack, null_mut()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut action = sigaction_t {
+        sa_sigaction: handle_signal as *const () as usize,
+        sa_mask: sigset_t { val: [0; 16] },
+        // The default action comes back for the handler's own raise
+        sa_flags: SA_SIGINFO | SA_ONSTACK | SA_RESETHAND,
+        sa_restorer: None
+    };
+    unsafe { sigemptyset(&mut action.sa_mask); }
+
+    for &signal in SIGNALS.iter() {
+        if unsafe { sigaction(signal, &action, null_mut()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Writes the time, `reason` and a backtrace of the caller to stderr and
+/// the crash log. Only the first call does anything, so a panic handler
+/// can call it and then abort without a second report
+pub fn report(reason: &dyn Display) {
+    if REPORTED.swap(true, Ordering::AcqRel) {
+        return;
+    }
+
+    let backtrace = Backtrace::capture();
+    let _ = write_report(&mut Fd(STDERR), reason, &backtrace);
+
+    let path = CRASH_LOG.load(Ordering::Acquire);
+    if !path.is_null() {
+        let fd = unsafe { open(path, O_WRONLY | O_CREAT | O_APPEND | O_CLOEXEC, 0o644) };
+        if fd < 0 {
+            let error = io::Error::last_os_error();
+            let _ = fmt::Write::write_fmt(
+                &mut Fd(STDERR),
+                format_args!("could not write crash log: {}\n", error)
+            );
+            return;
+        }
+        let _ = write_report(&mut Fd(fd), reason, &backtrace);
+        unsafe { close(fd); }
+    }
+}
+
+fn write_report(out: &mut Fd, reason: &dyn Display, backtrace: &Backtrace) -> FmtResult {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    fmt::Write::write_fmt(
+        out,
+        format_args!("{} crash: {}\nbacktrace:\n{}\n", Utc(now), reason, backtrace)
+    )
+}
+
+extern "C" fn handle_signal(signal: c_int, info: *mut siginfo_t, _context: *mut c_void) {
+    report(&Signal {
+        signal: signal,
+        address: unsafe { (*info).si_addr as usize }
+    });
+
+    // SA_RESETHAND restored the default action, so this ends the process
+    // with the original signal for the parent and any core dump
+    unsafe { raise(signal); }
+}
+
+impl Display for Signal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let (name, description) = match self.signal {
+            SIGSEGV => ("SIGSEGV", "invalid memory access"),
+            SIGBUS => ("SIGBUS", "misaligned or unbacked memory access"),
+            SIGILL => ("SIGILL", "illegal instruction"),
+            SIGFPE => ("SIGFPE", "arithmetic error"),
+            SIGABRT => return f.write_str("SIGABRT, aborted"),
+            _ => return write!(f, "signal {}", self.signal)
+        };
+        write!(f, "{}, {} at {:#x}", name, description, self.address)
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{
+    error::Error,
+    ffi::{CStr, CString},
+    io::{self, ErrorKind},
+    string::String,
+    sync::RwLock
+};
+use c::{
+    stdlib::{getenv, setenv, unsetenv},
+    types::{c_char, c_int}
+};
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    iter::FusedIterator,
+    ops::Range,
+    ptr::null_mut,
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering}
+};
+
+mod flags;
+pub use flags::*;
+
+static ARGC: AtomicUsize = AtomicUsize::new(0);
+static ARGV: AtomicPtr<*const c_char> = AtomicPtr::new(null_mut());
+
+// getenv hands out pointers into the environment that setenv may free, so
+// readers copy the value out while holding this
+static ENV_LOCK: RwLock<()> = RwLock::new(());
+
+/// glibc passes the program's arguments to `.init_array` functions, so they
+/// can be picked up without `main` having to hand them over
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[used]
+#[link_section = ".init_array.00099"]
+static ARGV_INIT_ARRAY: extern "C" fn(c_int, *const *const c_char, *const *const c_char) = {
+    extern "C" fn init(argc: c_int, argv: *const *const c_char, _envp: *const *const c_char) {
+        ARGC.store(argc as usize, Ordering::Relaxed);
+        ARGV.store(argv as *mut _, Ordering::Relaxed);
+    }
+    init
+};
+
+/// Stores the arguments `main` was given. Only needed where the arguments
+/// can't be picked up before `main` runs, which glibc allows, so anything
+/// captured already is kept
+///
+/// # Safety
+///
+/// `argv` must point to `argc` C strings that live as long as the program,
+/// as the ones passed to `main` do
+pub unsafe fn init_args(argc: c_int, argv: *const *const c_char) {
+    if ARGV.compare_exchange(
+        null_mut(),
+        argv as *mut _,
+        Ordering::Relaxed,
+        Ordering::Relaxed
+    ).is_ok() {
+        ARGC.store(argc as usize, Ordering::Relaxed);
+    }
+}
+
+/// The program's arguments, starting with its name, as C strings
+#[derive(Clone)]
+pub struct ArgsCStr {
+    argv: *const *const c_char,
+    range: Range<usize>
+}
+
+/// The program's arguments, starting with its name. Iterating panics on
+/// an argument that isn't UTF-8; use `args_cstr` to handle those
+#[derive(Clone)]
+pub struct Args {
+    inner: ArgsCStr
+}
+
+/// Why `var` found no usable value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VarError {
+    NotPresent,
+    /// The value is there but isn't UTF-8. The bytes are handed back
+    NotUnicode(CString)
+}
+
+pub fn args_cstr() -> ArgsCStr {
+    ArgsCStr {
+        argv: ARGV.load(Ordering::Relaxed),
+        range: 0..ARGC.load(Ordering::Relaxed)
+    }
+}
+
+pub fn args() -> Args {
+    Args { inner: args_cstr() }
+}
+
+impl ArgsCStr {
+    fn get(&self, i: usize) -> &'static CStr {
+        unsafe { CStr::from_ptr(*self.argv.add(i)) }
+    }
+}
+
+impl Iterator for ArgsCStr {
+    type Item = &'static CStr;
+
+    fn next(&mut self) -> Option<&'static CStr> {
+        self.range.next().map(|i| self.get(i))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for ArgsCStr {
+    fn next_back(&mut self) -> Option<&'static CStr> {
+        self.range.next_back().map(|i| self.get(i))
+    }
+}
+
+impl ExactSizeIterator for ArgsCStr {}
+impl FusedIterator for ArgsCStr {}
+
+fn to_str(arg: &'static CStr) -> &'static str {
+    match arg.to_str() {
+        Ok(arg) => arg,
+        Err(_) => panic!("argument is not valid UTF-8: {:?}", arg)
+    }
+}
+
+impl Iterator for Args {
+    type Item = &'static str;
+
+    fn next(&mut self) -> Option<&'static str> {
+        self.inner.next().map(to_str)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Args {
+    fn next_back(&mut self) -> Option<&'static str> {
+        self.inner.next_back().map(to_str)
+    }
+}
+
+impl ExactSizeIterator for Args {}
+impl FusedIterator for Args {}
+
+/// Copies the value of the environment variable `key`
+#[cfg_attr(feature = "track-allocations", track_caller)]
+pub fn var_cstr(key: &CStr) -> Option<CString> {
+    let _lock = ENV_LOCK.read();
+    let value = unsafe { getenv(key.as_ptr()) };
+    if value.is_null() {
+        None
+    } else {
+        Some(unsafe { CStr::from_ptr(value) }.to_owned())
+    }
+}
+
+/// Copies the value of the environment variable `key`. A key that can't
+/// be passed to C is never present
+#[cfg_attr(feature = "track-allocations", track_caller)]
+pub fn var(key: &str) -> Result<String, VarError> {
+    let key = CString::new(key).map_err(|_| VarError::NotPresent)?;
+    let value = var_cstr(&key).ok_or(VarError::NotPresent)?;
+    match String::from_utf8(value.into_bytes()) {
+        Ok(value) => Ok(value),
+        Err(e) => Err(VarError::NotUnicode(CString::from_array(e.into_bytes()).unwrap()))
+    }
+}
+
+fn env_key(key: &str) -> io::Result<CString> {
+    if key.is_empty() || key.contains('=') {
+        return Err(io::Error::new(ErrorKind::InvalidInput));
+    }
+    CString::new(key).map_err(|_| io::Error::new(ErrorKind::InvalidInput))
+}
+
+/// Sets an environment variable for this process and the ones it starts.
+/// C code calling `getenv` on another thread meanwhile isn't protected
+pub fn set_var(key: &str, value: &str) -> io::Result<()> {
+    let key = env_key(key)?;
+    let value = CString::new(value).map_err(|_| io::Error::new(ErrorKind::InvalidInput))?;
+
+    let _lock = ENV_LOCK.write();
+    if unsafe { setenv(key.as_ptr(), value.as_ptr(), 1) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Removes an environment variable. Removing one that isn't set succeeds
+pub fn remove_var(key: &str) -> io::Result<()> {
+    let key = env_key(key)?;
+
+    let _lock = ENV_LOCK.write();
+    if unsafe { unsetenv(key.as_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+impl Display for VarError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            VarError::NotPresent => f.write_str("environment variable not found"),
+            VarError::NotUnicode(_) => f.write_str("environment variable is not valid UTF-8")
+        }
+    }
+}
+
+impl Error for VarError {}
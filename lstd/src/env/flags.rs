@@ -0,0 +1,265 @@
+use crate::{
+    error::Error,
+    format,
+    fs::PathBuf,
+    string::String
+};
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    str::FromStr
+};
+
+/// A type a `flags!` field can have
+pub trait FlagValue: Sized {
+    /// Switches are set by the bare flag, as in `--validation`, rather than
+    /// taking a value
+    const SWITCH: bool = false;
+
+    /// Shown after the flag in the usage text
+    const HINT: &'static str;
+
+    fn parse_flag(value: &str) -> Option<Self>;
+}
+
+/// Why `flags!` couldn't parse the command line
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FlagError {
+    /// `--help` or `-h` was given
+    Help,
+    Unknown(String),
+    /// Something that isn't a flag, such as a stray value
+    Unexpected(String),
+    MissingValue(&'static str),
+    InvalidValue {
+        flag: &'static str,
+        value: String
+    }
+}
+
+macro_rules! impl_flag_value {
+    ($hint:literal: $($ty:ty),*) => {$(
+        impl FlagValue for $ty {
+            const HINT: &'static str = $hint;
+
+            fn parse_flag(value: &str) -> Option<$ty> {
+                <$ty as FromStr>::from_str(value).ok()
+            }
+        }
+    )*}
+}
+
+impl_flag_value!("<int>": u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+impl_flag_value!("<number>": f32, f64);
+
+impl FlagValue for bool {
+    const SWITCH: bool = true;
+    const HINT: &'static str = "";
+
+    fn parse_flag(value: &str) -> Option<bool> {
+        match value {
+            "true" | "yes" | "1" => Some(true),
+            "false" | "no" | "0" => Some(false),
+            _ => None
+        }
+    }
+}
+
+impl FlagValue for String {
+    const HINT: &'static str = "<string>";
+
+    fn parse_flag(value: &str) -> Option<String> {
+        Some(String::from(value))
+    }
+}
+
+impl FlagValue for PathBuf {
+    const HINT: &'static str = "<path>";
+
+    fn parse_flag(value: &str) -> Option<PathBuf> {
+        Some(PathBuf::from(value))
+    }
+}
+
+/// A flag that may be left out, with `None` as its default
+impl<T: FlagValue> FlagValue for Option<T> {
+    const SWITCH: bool = T::SWITCH;
+    const HINT: &'static str = T::HINT;
+
+    fn parse_flag(value: &str) -> Option<Option<T>> {
+        T::parse_flag(value).map(Some)
+    }
+}
+
+/// Writes a field name as the flag for it
+struct FlagName<'a>(&'a str);
+
+impl Display for FlagName<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("--")?;
+        for (i, part) in self.0.split('_').enumerate() {
+            if i > 0 { f.write_str("-")?; }
+            f.write_str(part)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits `--name=value` into its name and value, and `--name` into just
+/// a name
+#[doc(hidden)]
+pub fn __split_flag(arg: &str) -> Result<(&str, Option<&str>), FlagError> {
+    if arg == "--help" || arg == "-h" {
+        return Err(FlagError::Help);
+    }
+
+    let flag = match arg.strip_prefix("--") {
+        Some(flag) if !flag.is_empty() => flag,
+        _ => return Err(FlagError::Unexpected(String::from(arg)))
+    };
+    match flag.find('=') {
+        Some(i) => Ok((&flag[..i], Some(&flag[i + 1..]))),
+        None => Ok((flag, None))
+    }
+}
+
+/// Whether `--name` refers to `field`, with dashes in the flag standing in
+/// for underscores in the field
+#[doc(hidden)]
+pub fn __flag_matches(name: &str, field: &str) -> bool {
+    name.len() == field.len()
+        && name.bytes().zip(field.bytes()).all(|(a, b)| a == b || (a == b'-' && b == b'_'))
+}
+
+/// Parses the value of `flag`, taking it from the next argument unless it
+/// was given inline or the flag is a switch
+#[doc(hidden)]
+pub fn __parse_flag<'a, T: FlagValue, I: Iterator<Item = &'a str>>(
+    flag: &'static str,
+    inline: Option<&str>,
+    rest: &mut I
+) -> Result<T, FlagError> {
+    let value = match inline {
+        Some(value) => value,
+        None if T::SWITCH => "true",
+        None => rest.next().ok_or(FlagError::MissingValue(flag))?
+    };
+    T::parse_flag(value).ok_or_else(|| FlagError::InvalidValue {
+        flag: flag,
+        value: String::from(value)
+    })
+}
+
+/// One line of usage text
+#[doc(hidden)]
+pub fn __usage_line(usage: &mut String, field: &str, hint: &str, doc: &[&str], default: &str) {
+    use core::fmt::Write;
+
+    let flag = if hint.is_empty() {
+        format!("{}", FlagName(field))
+    } else {
+        format!("{} {}", FlagName(field), hint)
+    };
+
+    let _ = write!(usage, "  {:<24}", flag);
+    for line in doc {
+        let _ = write!(usage, " {}", line.trim());
+    }
+    let _ = writeln!(usage, " [default: {}]", default);
+}
+
+/// Declares a struct of command line flags. Each field becomes a
+/// `--field-name` flag, with dashes for underscores, and starts out as its
+/// default. `bool` fields are switches and `Option` fields may be left out
+///
+/// ```ignore
+/// flags! {
+///     struct Options {
+///         /// Window width in pixels
+///         width: u16 = 640,
+///         /// Index of the GPU to use
+///         gpu: Option<usize> = None,
+///         validation: bool = false
+///     }
+/// }
+///
+/// let options = Options::parse(env::args().skip(1))?;
+/// ```
+#[macro_export]
+macro_rules! flags {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[doc = $doc:literal])*
+                $field:ident: $ty:ty = $default:expr
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $(
+                $(#[doc = $doc])*
+                pub $field: $ty
+            ),*
+        }
+
+        impl $name {
+            /// Parses `--flag value` and `--flag=value` pairs. Switches
+            /// can be given bare, as in `--flag`
+            pub fn parse<'a, I>(args: I) -> ::core::result::Result<$name, $crate::env::FlagError>
+            where
+                I: ::core::iter::IntoIterator<Item = &'a str>
+            {
+                let mut flags = $name { $($field: $default),* };
+                let mut args = args.into_iter();
+                while let ::core::option::Option::Some(arg) = args.next() {
+                    let (name, value) = $crate::env::__split_flag(arg)?;
+                    $(
+                        if $crate::env::__flag_matches(name, stringify!($field)) {
+                            flags.$field = $crate::env::__parse_flag::<$ty, _>(
+                                stringify!($field),
+                                value,
+                                &mut args
+                            )?;
+                            continue;
+                        }
+                    )*
+                    return ::core::result::Result::Err(
+                        $crate::env::FlagError::Unknown($crate::string::String::from(arg))
+                    );
+                }
+                ::core::result::Result::Ok(flags)
+            }
+
+            /// The flags with their descriptions and defaults, a line each
+            pub fn usage() -> $crate::string::String {
+                let mut usage = $crate::string::String::new();
+                $(
+                    $crate::env::__usage_line(
+                        &mut usage,
+                        stringify!($field),
+                        <$ty as $crate::env::FlagValue>::HINT,
+                        &[$($doc),*],
+                        stringify!($default)
+                    );
+                )*
+                usage
+            }
+        }
+    };
+}
+
+impl Display for FlagError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            FlagError::Help => f.write_str("help requested"),
+            FlagError::Unknown(flag) => write!(f, "unknown flag {}", flag),
+            FlagError::Unexpected(arg) => write!(f, "unexpected argument {}", arg),
+            FlagError::MissingValue(flag) => write!(f, "{} needs a value", FlagName(flag)),
+            FlagError::InvalidValue { flag, value } =>
+                write!(f, "invalid value {:?} for {}", value, FlagName(flag))
+        }
+    }
+}
+
+impl Error for FlagError {}
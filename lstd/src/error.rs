@@ -0,0 +1,88 @@
+use crate::{
+    ffi::CStr,
+    io::ErrorKind
+};
+use c::{
+    errno::*,
+    string::strerror_r,
+    types::{c_char, c_int}
+};
+use core::fmt::{self, Display, Formatter};
+
+/// Implemented by every error type, so they can be boxed, printed and
+/// walked through `source`
+pub use core::error::Error;
+
+/// An error from the OS, carrying the errno it came from, or one lstd
+/// raised itself with only a kind. `io::Error` is this type
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IoError {
+    kind: ErrorKind,
+    code: Option<c_int>
+}
+
+impl IoError {
+    pub fn new(kind: ErrorKind) -> IoError {
+        IoError { kind: kind, code: None }
+    }
+
+    /// Wraps an errno value
+    pub fn from_raw_os_error(code: c_int) -> IoError {
+        let kind = match code {
+            EPERM | EACCES => ErrorKind::PermissionDenied,
+            ENOENT         => ErrorKind::NotFound,
+            EEXIST         => ErrorKind::AlreadyExists,
+            EINTR          => ErrorKind::Interrupted,
+            EINVAL         => ErrorKind::InvalidInput,
+            _              => ErrorKind::Other
+        };
+        IoError { kind: kind, code: Some(code) }
+    }
+
+    /// The error left in errno by the last failed call on this thread
+    pub fn last_os_error() -> IoError {
+        IoError::from_raw_os_error(errno())
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn raw_os_error(&self) -> Option<c_int> {
+        self.code
+    }
+}
+
+impl From<ErrorKind> for IoError {
+    fn from(kind: ErrorKind) -> IoError {
+        IoError::new(kind)
+    }
+}
+
+impl Display for IoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(code) = self.code {
+            let mut buf = [0 as c_char; 128];
+            let message = unsafe {
+                CStr::from_ptr(strerror_r(code, buf.as_mut_ptr(), buf.len()))
+            };
+            let message = message.to_str().unwrap_or("unknown error");
+            return write!(f, "{} (os error {})", message, code);
+        }
+
+        f.write_str(match self.kind {
+            ErrorKind::NotFound         => "entity not found",
+            ErrorKind::PermissionDenied => "permission denied",
+            ErrorKind::AlreadyExists    => "entity already exists",
+            ErrorKind::Interrupted      => "operation interrupted",
+            ErrorKind::InvalidInput     => "invalid input parameter",
+            ErrorKind::InvalidData      => "invalid data",
+            ErrorKind::UnexpectedEof    => "unexpected end of file",
+            ErrorKind::WriteZero        => "write zero",
+            ErrorKind::Unsupported      => "unsupported",
+            ErrorKind::Other            => "other error"
+        })
+    }
+}
+
+impl Error for IoError {}
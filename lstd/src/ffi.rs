@@ -0,0 +1,250 @@
+use crate::{
+    container::Array,
+    error::Error,
+    string::String
+};
+use c::types::c_char;
+use core::{
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    ops::Deref,
+    slice::from_raw_parts,
+    str::{from_utf8, Utf8Error}
+};
+
+/// A borrowed C string: bytes without interior NULs, followed by a NUL.
+/// Create one with `cstr!` for literals, `CStr::from_ptr` for strings that
+/// come from C, or borrow one from a `CString`
+#[repr(transparent)]
+pub struct CStr {
+    // Includes the terminator
+    bytes: [u8]
+}
+
+/// An owned C string, built from Rust data that is checked for interior
+/// NULs
+#[derive(Clone, PartialEq, Eq)]
+pub struct CString {
+    // Includes the terminator
+    bytes: Array<u8>
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FromBytesWithNulError {
+    InteriorNul(usize),
+    NotNulTerminated
+}
+
+/// The bytes given to `CString::new` contained a NUL at `position`. The
+/// bytes are handed back
+#[derive(Debug)]
+pub struct NulError {
+    position: usize,
+    bytes: Array<u8>
+}
+
+impl CStr {
+    /// Checks that `bytes` ends in its only NUL
+    pub const fn from_bytes_with_nul(bytes: &[u8]) -> Result<&CStr, FromBytesWithNulError> {
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == 0 {
+                if i + 1 == bytes.len() {
+                    return Ok(unsafe { CStr::from_bytes_with_nul_unchecked(bytes) });
+                }
+                return Err(FromBytesWithNulError::InteriorNul(i));
+            }
+            i += 1;
+        }
+
+        Err(FromBytesWithNulError::NotNulTerminated)
+    }
+
+    /// # Safety
+    ///
+    /// `bytes` must end in a NUL and contain no other
+    pub const unsafe fn from_bytes_with_nul_unchecked(bytes: &[u8]) -> &CStr {
+        &*(bytes as *const [u8] as *const CStr)
+    }
+
+    /// Borrows a string owned by C code
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a NUL-terminated string that outlives `'a`
+    pub unsafe fn from_ptr<'a>(ptr: *const c_char) -> &'a CStr {
+        let mut len = 0;
+        while *ptr.add(len) != 0 {
+            len += 1;
+        }
+
+        CStr::from_bytes_with_nul_unchecked(from_raw_parts(ptr as *const u8, len + 1))
+    }
+
+    pub const fn as_ptr(&self) -> *const c_char {
+        self.bytes.as_ptr() as *const c_char
+    }
+
+    /// The bytes without the terminator
+    pub fn to_bytes(&self) -> &[u8] {
+        &self.bytes[..self.bytes.len() - 1]
+    }
+
+    pub fn to_bytes_with_nul(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn to_str(&self) -> Result<&str, Utf8Error> {
+        from_utf8(self.to_bytes())
+    }
+
+    /// Copies the string into a `String`, replacing invalid UTF-8 with
+    /// U+FFFD
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn to_string_lossy(&self) -> String {
+        let mut result = String::with_capacity(self.to_bytes().len());
+        for chunk in self.to_bytes().utf8_chunks() {
+            result.push_str(chunk.valid());
+            if !chunk.invalid().is_empty() {
+                result.push('\u{fffd}');
+            }
+        }
+        result
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn to_owned(&self) -> CString {
+        CString { bytes: Array::from_slice(&self.bytes) }
+    }
+}
+
+impl PartialEq for CStr {
+    fn eq(&self, other: &CStr) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl Eq for CStr {}
+
+impl Debug for CStr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("\"")?;
+        for chunk in self.to_bytes().utf8_chunks() {
+            for c in chunk.valid().chars() {
+                write!(f, "{}", c.escape_debug())?;
+            }
+            for b in chunk.invalid() {
+                write!(f, "\\x{:02x}", b)?;
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+impl AsRef<CStr> for CStr {
+    fn as_ref(&self) -> &CStr {
+        self
+    }
+}
+
+impl CString {
+    /// Copies `bytes` and appends a NUL, failing if they already contain
+    /// one
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn new<T: AsRef<[u8]>>(bytes: T) -> Result<CString, NulError> {
+        CString::from_array(Array::from_slice(bytes.as_ref()))
+    }
+
+    /// Takes over `bytes` and appends a NUL, failing if they already
+    /// contain one
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn from_array(bytes: Array<u8>) -> Result<CString, NulError> {
+        match bytes.iter().position(|b| *b == 0) {
+            Some(position) => Err(NulError { position: position, bytes: bytes }),
+            None => Ok(unsafe { CString::from_array_unchecked(bytes) })
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `bytes` must not contain a NUL
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub unsafe fn from_array_unchecked(mut bytes: Array<u8>) -> CString {
+        bytes.push(0);
+        CString { bytes: bytes }
+    }
+
+    pub fn as_c_str(&self) -> &CStr {
+        unsafe { CStr::from_bytes_with_nul_unchecked(&self.bytes) }
+    }
+
+    /// The bytes without the terminator
+    pub fn into_bytes(mut self) -> Array<u8> {
+        self.bytes.pop();
+        self.bytes
+    }
+
+    /// Converts back into a `String`, handing the bytes back if they are
+    /// not UTF-8
+    pub fn into_string(self) -> Result<String, Array<u8>> {
+        String::from_utf8(self.into_bytes()).map_err(|e| e.into_bytes())
+    }
+}
+
+impl Deref for CString {
+    type Target = CStr;
+
+    fn deref(&self) -> &CStr {
+        self.as_c_str()
+    }
+}
+
+impl AsRef<CStr> for CString {
+    fn as_ref(&self) -> &CStr {
+        self.as_c_str()
+    }
+}
+
+impl Debug for CString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(self.as_c_str(), f)
+    }
+}
+
+impl From<&CStr> for CString {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn from(s: &CStr) -> CString {
+        s.to_owned()
+    }
+}
+
+impl NulError {
+    /// Where the first NUL was found
+    pub fn nul_position(&self) -> usize {
+        self.position
+    }
+
+    pub fn into_array(self) -> Array<u8> {
+        self.bytes
+    }
+}
+
+impl Display for NulError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "nul byte found at position {}", self.position)
+    }
+}
+
+impl Error for NulError {}
+
+impl Display for FromBytesWithNulError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            FromBytesWithNulError::InteriorNul(position) =>
+                write!(f, "nul byte found at position {}", position),
+            FromBytesWithNulError::NotNulTerminated =>
+                f.write_str("data is not nul terminated")
+        }
+    }
+}
+
+impl Error for FromBytesWithNulError {}
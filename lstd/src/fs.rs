@@ -0,0 +1,205 @@
+use crate::{
+    ffi::CStr,
+    io::{self, Error, ErrorKind, Metadata, Result}
+};
+use c::{
+    dirent::*,
+    errno::{errno, __errno_location},
+    stat::{lstat, mkdir, stat},
+    stdio,
+    types::c_int,
+    unistd::{rmdir, unlink}
+};
+use core::{
+    mem::MaybeUninit,
+    ops::Drop
+};
+
+mod path;
+mod temp;
+pub use path::*;
+pub use temp::*;
+
+/// Iterator over the entries of a directory, other than `.` and `..`.
+/// Entries whose names are not UTF-8 come out as `InvalidData` errors
+pub struct ReadDir {
+    dir: *mut DIR,
+    path: PathBuf
+}
+
+unsafe impl Send for ReadDir {}
+
+pub struct DirEntry {
+    path: PathBuf,
+    file_type: u8
+}
+
+pub fn read_dir<P: AsRef<Path>>(path: P) -> Result<ReadDir> {
+    let path = path.as_ref();
+    let dir = unsafe { opendir(path.to_c_string()?.as_ptr()) };
+    if dir.is_null() {
+        return Err(Error::last_os_error());
+    }
+
+    Ok(ReadDir { dir: dir, path: path.to_path_buf() })
+}
+
+impl Iterator for ReadDir {
+    type Item = Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Result<DirEntry>> {
+        loop {
+            // readdir only sets errno on failure, so clear it to tell the
+            // end of the directory apart from an error
+            let entry = unsafe {
+                *__errno_location() = 0;
+                readdir(self.dir)
+            };
+            if entry.is_null() {
+                return match errno() {
+                    0 => None,
+                    code => Some(Err(Error::from_raw_os_error(code)))
+                };
+            }
+
+            let (name, file_type) = unsafe {
+                (CStr::from_ptr((*entry).d_name.as_ptr()), (*entry).d_type)
+            };
+            let name = match name.to_str() {
+                Ok(name) => name,
+                Err(_) => return Some(Err(Error::new(ErrorKind::InvalidData)))
+            };
+            if name == "." || name == ".." {
+                continue;
+            }
+
+            return Some(Ok(DirEntry { path: self.path.join(name), file_type: file_type }));
+        }
+    }
+}
+
+impl Drop for ReadDir {
+    fn drop(&mut self) {
+        unsafe { closedir(self.dir); }
+    }
+}
+
+impl DirEntry {
+    /// The directory passed to `read_dir` joined with the entry's name
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn file_name(&self) -> &str {
+        self.path.file_name().unwrap_or("")
+    }
+
+    /// Looks up the entry itself, not what it links to
+    pub fn metadata(&self) -> Result<Metadata> {
+        symlink_metadata(&self.path)
+    }
+
+    /// These use the type readdir reported and only fall back to a lookup
+    /// on filesystems that don't report one
+    pub fn is_dir(&self) -> bool {
+        self.is_type(DT_DIR, Metadata::is_dir)
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.is_type(DT_REG, Metadata::is_file)
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.is_type(DT_LNK, Metadata::is_symlink)
+    }
+
+    fn is_type(&self, file_type: u8, test: fn(&Metadata) -> bool) -> bool {
+        if self.file_type == DT_UNKNOWN {
+            self.metadata().is_ok_and(|m| test(&m))
+        } else {
+            self.file_type == file_type
+        }
+    }
+}
+
+/// Follows symlinks
+pub fn metadata<P: AsRef<Path>>(path: P) -> Result<Metadata> {
+    io::metadata(&path.as_ref().to_c_string()?)
+}
+
+/// Describes a symlink itself rather than what it points to
+pub fn symlink_metadata<P: AsRef<Path>>(path: P) -> Result<Metadata> {
+    let path = path.as_ref().to_c_string()?;
+    let mut buf = MaybeUninit::<stat>::uninit();
+    unsafe {
+        if lstat(path.as_ptr(), buf.as_mut_ptr()) != 0 {
+            return Err(Error::last_os_error());
+        }
+        Ok(Metadata::from_stat(&buf.assume_init()))
+    }
+}
+
+pub fn exists<P: AsRef<Path>>(path: P) -> bool {
+    metadata(path).is_ok()
+}
+
+fn check(rc: c_int) -> Result<()> {
+    if rc != 0 { Err(Error::last_os_error()) } else { Ok(()) }
+}
+
+pub fn create_dir<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref().to_c_string()?;
+    check(unsafe { mkdir(path.as_ptr(), 0o777) })
+}
+
+/// Creates a directory along with any missing parents. A directory that
+/// is already there counts as success
+pub fn create_dir_all<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    if path.as_str().is_empty() || path.is_dir() {
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    match create_dir(path) {
+        // Someone else may have created it in the meantime
+        Err(_) if path.is_dir() => Ok(()),
+        result => result
+    }
+}
+
+pub fn remove_file<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref().to_c_string()?;
+    check(unsafe { unlink(path.as_ptr()) })
+}
+
+/// Removes an empty directory
+pub fn remove_dir<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref().to_c_string()?;
+    check(unsafe { rmdir(path.as_ptr()) })
+}
+
+/// Removes a directory and everything in it. Symlinks are removed, not
+/// followed
+pub fn remove_dir_all<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    for entry in read_dir(path)? {
+        let entry = entry?;
+        if entry.is_dir() {
+            remove_dir_all(entry.path())?;
+        } else {
+            remove_file(entry.path())?;
+        }
+    }
+    remove_dir(path)
+}
+
+/// Moves `from` to `to`, replacing `to` if it is a file. Both must be on
+/// the same filesystem
+pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
+    let from = from.as_ref().to_c_string()?;
+    let to = to.as_ref().to_c_string()?;
+    check(unsafe { stdio::rename(from.as_ptr(), to.as_ptr()) })
+}
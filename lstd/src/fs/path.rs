@@ -0,0 +1,345 @@
+use crate::{
+    ffi::CString,
+    io::{self, Error, ErrorKind},
+    string::String
+};
+use core::{
+    borrow::Borrow,
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    iter::FusedIterator,
+    ops::Deref,
+    str::Split
+};
+
+/// A borrowed filesystem path. Components are separated by `/`, and paths
+/// are kept as UTF-8 so they can be printed and compared as strings
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Path {
+    inner: str
+}
+
+/// An owned, growable `Path`
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PathBuf {
+    inner: String
+}
+
+impl Path {
+    pub fn new<S: AsRef<str> + ?Sized>(s: &S) -> &Path {
+        unsafe { &*(s.as_ref() as *const str as *const Path) }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.inner
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn to_path_buf(&self) -> PathBuf {
+        PathBuf { inner: String::from(&self.inner) }
+    }
+
+    /// Copies the path for passing to C, failing with `InvalidInput` if it
+    /// contains a NUL
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn to_c_string(&self) -> io::Result<CString> {
+        CString::new(&self.inner).map_err(|_| Error::new(ErrorKind::InvalidInput))
+    }
+
+    pub fn is_absolute(&self) -> bool {
+        self.inner.starts_with('/')
+    }
+
+    pub fn is_relative(&self) -> bool {
+        !self.is_absolute()
+    }
+
+    /// The path without trailing separators, except for the root itself
+    fn trimmed(&self) -> &str {
+        let trimmed = self.inner.trim_end_matches('/');
+        if trimmed.is_empty() && self.is_absolute() { "/" } else { trimmed }
+    }
+
+    /// The path without its last component. `None` for the root and for
+    /// the empty path, and an empty path for a single relative component
+    pub fn parent(&self) -> Option<&Path> {
+        let trimmed = self.trimmed();
+        if trimmed.is_empty() || trimmed == "/" {
+            return None;
+        }
+
+        match trimmed.rfind('/') {
+            Some(0) => Some(Path::new("/")),
+            Some(i) => Some(Path::new(trimmed[..i].trim_end_matches('/'))),
+            None => Some(Path::new(""))
+        }
+    }
+
+    /// The last component, unless the path ends in `..` or is the root
+    pub fn file_name(&self) -> Option<&str> {
+        let trimmed = self.trimmed();
+        let name = match trimmed.rfind('/') {
+            Some(i) => &trimmed[i + 1..],
+            None => trimmed
+        };
+        if name.is_empty() || name == "." || name == ".." { None } else { Some(name) }
+    }
+
+    /// The file name without its extension. A leading dot, as in
+    /// `.gitignore`, does not start an extension
+    pub fn file_stem(&self) -> Option<&str> {
+        let name = self.file_name()?;
+        match name.rfind('.') {
+            Some(0) | None => Some(name),
+            Some(i) => Some(&name[..i])
+        }
+    }
+
+    /// What follows the last dot of the file name
+    pub fn extension(&self) -> Option<&str> {
+        let name = self.file_name()?;
+        match name.rfind('.') {
+            Some(0) | None => None,
+            Some(i) => Some(&name[i + 1..])
+        }
+    }
+
+    /// Appends `path` with a separator in between. An absolute `path`
+    /// replaces this one instead
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        let mut result = self.to_path_buf();
+        result.push(path);
+        result
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn with_extension(&self, extension: &str) -> PathBuf {
+        let mut result = self.to_path_buf();
+        result.set_extension(extension);
+        result
+    }
+
+    /// The non-empty components other than `.`, so `a//./b/` yields `a`
+    /// and `b`. The root of an absolute path is not a component
+    pub fn components(&self) -> Components<'_> {
+        Components { parts: self.inner.split('/') }
+    }
+
+    pub fn starts_with<P: AsRef<Path>>(&self, base: P) -> bool {
+        let mut base_components = base.as_ref().components();
+        let mut components = self.components();
+        if base.as_ref().is_absolute() != self.is_absolute() {
+            return false;
+        }
+
+        loop {
+            match (base_components.next(), components.next()) {
+                (None, _) => return true,
+                (Some(a), Some(b)) if a == b => {}
+                _ => return false
+            }
+        }
+    }
+
+    pub fn exists(&self) -> bool {
+        super::exists(self)
+    }
+
+    pub fn is_dir(&self) -> bool {
+        super::metadata(self).is_ok_and(|m| m.is_dir())
+    }
+
+    pub fn is_file(&self) -> bool {
+        super::metadata(self).is_ok_and(|m| m.is_file())
+    }
+}
+
+impl PathBuf {
+    pub fn new() -> PathBuf {
+        PathBuf { inner: String::new() }
+    }
+
+    pub fn as_path(&self) -> &Path {
+        Path::new(self.inner.as_str())
+    }
+
+    pub fn into_string(self) -> String {
+        self.inner
+    }
+
+    /// Appends `path` with a separator in between. An absolute `path`
+    /// replaces the whole buffer
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn push<P: AsRef<Path>>(&mut self, path: P) {
+        let path = path.as_ref();
+        if path.is_absolute() {
+            self.inner.clear();
+        } else if !self.inner.is_empty() && !self.inner.ends_with('/') {
+            self.inner.push('/');
+        }
+        self.inner.push_str(path.as_str());
+    }
+
+    /// Drops the last component. Returns false if there was none
+    pub fn pop(&mut self) -> bool {
+        let len = match self.parent() {
+            Some(parent) => parent.as_str().len(),
+            None => return false
+        };
+        self.inner.truncate(len);
+        true
+    }
+
+    /// Replaces the last component, or adds one if there is none
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn set_file_name(&mut self, name: &str) {
+        if self.file_name().is_some() {
+            self.pop();
+        }
+        self.push(name);
+    }
+
+    /// Replaces the extension, or removes it if `extension` is empty.
+    /// Returns false if there is no file name to change
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn set_extension(&mut self, extension: &str) -> bool {
+        let stem_end = match self.file_stem() {
+            Some(stem) => stem.as_ptr() as usize - self.inner.as_ptr() as usize + stem.len(),
+            None => return false
+        };
+
+        self.inner.truncate(stem_end);
+        if !extension.is_empty() {
+            self.inner.push('.');
+            self.inner.push_str(extension);
+        }
+        true
+    }
+}
+
+impl Deref for PathBuf {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        self.as_path()
+    }
+}
+
+impl Borrow<Path> for PathBuf {
+    fn borrow(&self) -> &Path {
+        self.as_path()
+    }
+}
+
+impl AsRef<Path> for Path {
+    fn as_ref(&self) -> &Path {
+        self
+    }
+}
+
+impl AsRef<Path> for PathBuf {
+    fn as_ref(&self) -> &Path {
+        self.as_path()
+    }
+}
+
+impl AsRef<Path> for str {
+    fn as_ref(&self) -> &Path {
+        Path::new(self)
+    }
+}
+
+impl AsRef<Path> for String {
+    fn as_ref(&self) -> &Path {
+        Path::new(self.as_str())
+    }
+}
+
+impl AsRef<str> for Path {
+    fn as_ref(&self) -> &str {
+        &self.inner
+    }
+}
+
+impl From<&str> for PathBuf {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn from(s: &str) -> PathBuf {
+        PathBuf { inner: String::from(s) }
+    }
+}
+
+impl From<String> for PathBuf {
+    fn from(s: String) -> PathBuf {
+        PathBuf { inner: s }
+    }
+}
+
+impl From<&Path> for PathBuf {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn from(path: &Path) -> PathBuf {
+        path.to_path_buf()
+    }
+}
+
+impl PartialEq<str> for Path {
+    fn eq(&self, other: &str) -> bool {
+        &self.inner == other
+    }
+}
+
+impl PartialEq<&str> for PathBuf {
+    fn eq(&self, other: &&str) -> bool {
+        self.inner == *other
+    }
+}
+
+impl Debug for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(&self.inner, f)
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(&self.inner, f)
+    }
+}
+
+impl Debug for PathBuf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(self.as_path(), f)
+    }
+}
+
+impl Display for PathBuf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(self.as_path(), f)
+    }
+}
+
+/// The iterator returned by `Path::components`
+pub struct Components<'a> {
+    parts: Split<'a, char>
+}
+
+impl<'a> Iterator for Components<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        self.parts.find(|part| !part.is_empty() && *part != ".")
+    }
+}
+
+impl<'a> DoubleEndedIterator for Components<'a> {
+    fn next_back(&mut self) -> Option<&'a str> {
+        loop {
+            let part = self.parts.next_back()?;
+            if !part.is_empty() && part != "." {
+                return Some(part);
+            }
+        }
+    }
+}
+
+impl<'a> FusedIterator for Components<'a> {}
@@ -0,0 +1,152 @@
+use crate::{
+    container::Array,
+    env,
+    io::{Error, ErrorKind, File, FileSeek, Read, Result, Seek, Write},
+    string::String
+};
+use super::{remove_dir_all, remove_file, Path, PathBuf};
+use c::{
+    stdlib::{mkdtemp, mkstemp},
+    types::c_char
+};
+use core::{
+    mem::{forget, replace},
+    ops::Drop
+};
+
+/// A directory that is removed along with its contents when dropped
+pub struct TempDir {
+    path: PathBuf
+}
+
+/// A file opened for reading and writing that is deleted when dropped
+pub struct TempFile {
+    file: File,
+    path: PathBuf
+}
+
+/// `$TMPDIR`, or `/tmp` if it isn't set
+pub fn temp_dir() -> PathBuf {
+    match env::var("TMPDIR") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from("/tmp")
+    }
+}
+
+/// Builds `<temp_dir>/<prefix>XXXXXX` as a NUL-terminated buffer that
+/// mkstemp and mkdtemp can fill in
+fn template(prefix: &str) -> Result<Array<u8>> {
+    let mut name = String::from(prefix);
+    name.push_str("XXXXXX");
+    let path = temp_dir().join(name.as_str());
+
+    let mut bytes = path.to_c_string()?.into_bytes();
+    bytes.push(0);
+    Ok(bytes)
+}
+
+fn template_path(mut bytes: Array<u8>) -> Result<PathBuf> {
+    bytes.pop();
+    String::from_utf8(bytes)
+        .map(PathBuf::from)
+        .map_err(|_| Error::new(ErrorKind::InvalidData))
+}
+
+impl TempDir {
+    pub fn new() -> Result<TempDir> {
+        TempDir::with_prefix("lstd-")
+    }
+
+    /// Creates a uniquely named directory whose name starts with `prefix`
+    pub fn with_prefix(prefix: &str) -> Result<TempDir> {
+        let mut bytes = template(prefix)?;
+        if unsafe { mkdtemp(bytes.as_mut_ptr() as *mut c_char) }.is_null() {
+            return Err(Error::last_os_error());
+        }
+
+        Ok(TempDir { path: template_path(bytes)? })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Keeps the directory around and returns its path
+    pub fn into_path(mut self) -> PathBuf {
+        let path = replace(&mut self.path, PathBuf::new());
+        forget(self);
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = remove_dir_all(&self.path);
+    }
+}
+
+impl TempFile {
+    pub fn new() -> Result<TempFile> {
+        TempFile::with_prefix("lstd-")
+    }
+
+    /// Creates and opens a uniquely named file whose name starts with
+    /// `prefix`
+    pub fn with_prefix(prefix: &str) -> Result<TempFile> {
+        let mut bytes = template(prefix)?;
+        let fd = unsafe { mkstemp(bytes.as_mut_ptr() as *mut c_char) };
+        if fd < 0 {
+            return Err(Error::last_os_error());
+        }
+
+        let path = template_path(bytes)?;
+        let file = unsafe { File::from_fd(fd, b"r+\0") };
+        match file {
+            Ok(file) => Ok(TempFile { file: file, path: path }),
+            Err(error) => {
+                let _ = remove_file(&path);
+                Err(error)
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn as_file(&self) -> &File {
+        &self.file
+    }
+
+    pub fn as_file_mut(&mut self) -> &mut File {
+        &mut self.file
+    }
+}
+
+impl Read for TempFile {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+impl Write for TempFile {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Seek for TempFile {
+    fn seek(&mut self, offset: i64, from: FileSeek) -> Result<u64> {
+        self.file.seek(offset, from)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = remove_file(&self.path);
+    }
+}
@@ -1,165 +1,337 @@
-use c::{
-    stdio,
-    types::*
+use crate::{
+    alloc::Allocator,
+    container::Array,
+    string::String
 };
 use core::{
-    fmt::{Write, Error},
-    ptr::null_mut,
-    ops::Drop
+    fmt::{self, Arguments},
+    mem::take,
+    str::from_utf8
 };
 
-const STRING_BUFFER_SIZE: usize = 1024;
-pub static mut STRING_BUFFER: StringWriter =
-    StringWriter { buf: [0_u8; STRING_BUFFER_SIZE], cursor: 0 };
+mod buffered;
+mod cursor;
+mod file;
+mod mmap;
+mod stdio;
+pub use buffered::*;
+pub use cursor::*;
+pub use file::*;
+pub use mmap::*;
+pub use stdio::*;
 
-pub fn print(s: &str) {
-    unsafe {
-        c::stdio::write(c::stdio::STDOUT, s.as_ptr() as *const _, s.len());
-    }
-}
+const DEFAULT_BUFFER_SIZE: usize = 8 * 1024;
 
-pub struct StringWriter {
-    pub buf: [u8; STRING_BUFFER_SIZE],
-    pub cursor: usize
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    NotFound,
+    PermissionDenied,
+    AlreadyExists,
+    Interrupted,
+    InvalidInput,
+    InvalidData,
+    UnexpectedEof,
+    WriteZero,
+    Unsupported,
+    Other
 }
 
-impl StringWriter {
-    pub fn reset_cursor(&mut self) {
-        self.cursor = 0;
+pub use crate::error::IoError as Error;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// A source of bytes
+pub trait Read {
+    /// Reads some bytes into `buf` and returns how many. Zero means the end
+    /// of the input, unless `buf` was empty
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+    /// Fills all of `buf`, failing with `UnexpectedEof` if the input ends
+    /// first
+    fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+        while !buf.is_empty() {
+            match self.read(buf) {
+                Ok(0) => return Err(Error::new(ErrorKind::UnexpectedEof)),
+                Ok(n) => buf = &mut buf[n..],
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e)
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends everything up to the end of the input to `buf` and returns
+    /// how many bytes that was
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn read_to_end<A: Allocator>(&mut self, buf: &mut Array<u8, A>) -> Result<usize> {
+        let start = buf.len();
+        let mut chunk = [0; DEFAULT_BUFFER_SIZE];
+        loop {
+            match self.read(&mut chunk) {
+                Ok(0) => return Ok(buf.len() - start),
+                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e)
+            }
+        }
+    }
+
+    /// Like `read_to_end`, but fails with `InvalidData` and leaves `buf`
+    /// untouched if the input is not UTF-8
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn read_to_string<A: Allocator>(&mut self, buf: &mut String<A>) -> Result<usize> {
+        append_utf8(buf, |bytes| self.read_to_end(bytes))
     }
 
-    pub fn add_terminator(&mut self) {
-        self.buf[self.cursor] = b'\0';
-        self.cursor += 1;
+    fn by_ref(&mut self) -> &mut Self where Self: Sized {
+        self
     }
 }
 
-impl Write for StringWriter {
-    fn write_str(&mut self, s: &str) -> Result<(), Error> {
-        if self.cursor + s.len() < STRING_BUFFER_SIZE {
-            for (i, ch) in s.as_bytes().iter().enumerate() {
-                self.buf[self.cursor + i] = *ch;
+/// A sink for bytes
+pub trait Write {
+    /// Writes some of `buf` and returns how many bytes were taken
+    fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+    /// Pushes out anything held in intermediate buffers
+    fn flush(&mut self) -> Result<()>;
+
+    /// Writes all of `buf`, failing with `WriteZero` if the sink stops
+    /// taking bytes
+    fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+        while !buf.is_empty() {
+            match self.write(buf) {
+                Ok(0) => return Err(Error::new(ErrorKind::WriteZero)),
+                Ok(n) => buf = &buf[n..],
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e)
             }
-            self.cursor += s.len();
         }
-        else {
-            panic!()
+        Ok(())
+    }
+
+    /// What `write!` and `writeln!` call
+    fn write_fmt(&mut self, args: Arguments<'_>) -> Result<()> {
+        struct Adapter<'a, W: ?Sized> {
+            inner: &'a mut W,
+            error: Result<()>
         }
 
-        Ok(())
+        impl<'a, W: Write + ?Sized> fmt::Write for Adapter<'a, W> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                match self.inner.write_all(s.as_bytes()) {
+                    Ok(()) => Ok(()),
+                    Err(e) => {
+                        self.error = Err(e);
+                        Err(fmt::Error)
+                    }
+                }
+            }
+        }
+
+        let mut adapter = Adapter { inner: self, error: Ok(()) };
+        match fmt::write(&mut adapter, args) {
+            Ok(()) => Ok(()),
+            Err(_) => match adapter.error {
+                Err(e) => Err(e),
+                Ok(()) => Err(Error::new(ErrorKind::Other))
+            }
+        }
+    }
+
+    fn by_ref(&mut self) -> &mut Self where Self: Sized {
+        self
     }
 }
 
-#[macro_export]
-macro_rules! print {
-    ($s:expr) => (print!($s,));
+/// A stream with a cursor that can be moved
+pub trait Seek {
+    /// Moves the cursor `offset` bytes from `from` and returns the new
+    /// position from the start
+    fn seek(&mut self, offset: i64, from: FileSeek) -> Result<u64>;
 
-    ($fmt:expr, $($expr:expr),*) => {{
-        use $crate::io::{STRING_BUFFER, StringWriter, print};
-        use core::{
-            fmt::{write, Write},
-            str::from_utf8_unchecked
-        };
+    /// The current position from the start
+    fn tell(&mut self) -> Result<u64> {
+        self.seek(0, FileSeek::Cur)
+    }
+
+    fn rewind(&mut self) -> Result<()> {
+        self.seek(0, FileSeek::Set).map(|_| ())
+    }
+}
 
-        unsafe {
-            STRING_BUFFER.reset_cursor();
-            let _ = write(
-                &mut STRING_BUFFER,
-                format_args!($fmt, $($expr),*)
-            );
-            STRING_BUFFER.add_terminator();
+/// A reader with an internal buffer, which allows reading up to a
+/// delimiter without giving back bytes read past it
+pub trait BufRead: Read {
+    /// Returns the buffered bytes, refilling the buffer first if it is
+    /// empty. An empty result means the end of the input
+    fn fill_buf(&mut self) -> Result<&[u8]>;
 
-            print(from_utf8_unchecked(&STRING_BUFFER.buf[0 .. STRING_BUFFER.cursor]));
+    /// Marks `amount` bytes returned by `fill_buf` as read
+    fn consume(&mut self, amount: usize);
+
+    /// Appends bytes to `buf` up to and including `delimiter` or the end of
+    /// the input, and returns how many were appended
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn read_until<A: Allocator>(&mut self, delimiter: u8, buf: &mut Array<u8, A>) -> Result<usize> {
+        let mut total = 0;
+        loop {
+            let (done, used) = {
+                let available = match self.fill_buf() {
+                    Ok(available) => available,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e)
+                };
+                match available.iter().position(|b| *b == delimiter) {
+                    Some(i) => {
+                        buf.extend_from_slice(&available[..=i]);
+                        (true, i + 1)
+                    }
+                    None => {
+                        buf.extend_from_slice(available);
+                        (available.is_empty(), available.len())
+                    }
+                }
+            };
+            self.consume(used);
+            total += used;
+            if done {
+                return Ok(total);
+            }
         }
+    }
 
-    }}
+    /// Appends the next line to `buf`, keeping its `\n`. Returns zero at
+    /// the end of the input
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn read_line<A: Allocator>(&mut self, buf: &mut String<A>) -> Result<usize> {
+        append_utf8(buf, |bytes| self.read_until(b'\n', bytes))
+    }
+
+    /// Iterates over the lines of the input without their `\n` or `\r\n`
+    fn lines(self) -> Lines<Self> where Self: Sized {
+        Lines { reader: self }
+    }
 }
 
-#[macro_export]
-macro_rules! println {
-    ($s:expr) => (println!($s,));
+/// Lets `f` append raw bytes to `buf`, and takes them back off if they are
+/// not UTF-8
+fn append_utf8<A, F>(buf: &mut String<A>, f: F) -> Result<usize>
+where
+    A: Allocator,
+    F: FnOnce(&mut Array<u8, A>) -> Result<usize>
+{
+    let bytes = unsafe { buf.as_mut_array() };
+    let start = bytes.len();
+    let result = f(bytes);
+    if from_utf8(&bytes[start..]).is_err() {
+        bytes.truncate(start);
+        return result.and(Err(Error::new(ErrorKind::InvalidData)));
+    }
+    result
+}
 
-    ($fmt:expr, $($expr:expr),*) => {{
-        $crate::print!(concat!($fmt, "\n"), $($expr),*);
-    }}
+impl<R: Read + ?Sized> Read for &mut R {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        (**self).read(buf)
+    }
 }
 
-pub enum FileMode {
-    Read,
-    Write,
-    Append
+impl<W: Write + ?Sized> Write for &mut W {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        (**self).write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        (**self).flush()
+    }
 }
 
-pub enum FileSeek {
-    Set,
-    Cur,
-    End
+impl<S: Seek + ?Sized> Seek for &mut S {
+    fn seek(&mut self, offset: i64, from: FileSeek) -> Result<u64> {
+        (**self).seek(offset, from)
+    }
 }
 
-pub struct File(*mut c_void, FileMode);
+impl<B: BufRead + ?Sized> BufRead for &mut B {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        (**self).fill_buf()
+    }
 
-impl File {
-    pub unsafe fn open(filename: &str, mode: FileMode) -> Option<File> {
-        let handle = {
-            let mode = match mode {
-                FileMode::Read   => b"rb\0",
-                FileMode::Write  => b"wb\0",
-                FileMode::Append => b"ab\0"
-            };
-            stdio::fopen(
-                filename.as_ptr() as *const _,
-                mode.as_ptr() as *mut _
-            )
-        };
-        if handle == null_mut() {
-            None
-        } else {
-            Some(File(handle, mode))
-        }
+    fn consume(&mut self, amount: usize) {
+        (**self).consume(amount)
     }
+}
 
-    pub fn read<'a>(&self, buf: &'a mut [u8]) -> Option<&'a [u8]> {
-        match self.1 {
-            FileMode::Write => return None,
-            _ => {}
-        };
-
-        let read = unsafe {
-            stdio::fread(
-                buf.as_mut_ptr() as *mut _,
-                1,
-                buf.len(),
-                self.0
-            )
-        };
-
-        if read == 0 {
-            None
-        } else {
-            Some(&buf[0..read])
-        }
+impl Read for &[u8] {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = buf.len().min(self.len());
+        buf[..n].copy_from_slice(&self[..n]);
+        *self = &self[n..];
+        Ok(n)
+    }
+}
+
+impl BufRead for &[u8] {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        Ok(*self)
     }
 
-    pub fn write(&self, buf: &[u8]) -> usize {
-        match self.1 {
-            FileMode::Read => return 0,
-            _ => {}
-        };
+    fn consume(&mut self, amount: usize) {
+        *self = &self[amount..];
+    }
+}
 
-        unsafe {
-            stdio::fwrite(
-                buf.as_ptr() as *mut _,
-                1,
-                buf.len(),
-                self.0
-            )
-        }
+/// Writing to a slice fills it from the front and shrinks it to what is
+/// left
+impl Write for &mut [u8] {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = buf.len().min(self.len());
+        let (head, tail) = take(self).split_at_mut(n);
+        head.copy_from_slice(&buf[..n]);
+        *self = tail;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
     }
 }
 
-impl Drop for File {
-    fn drop(&mut self) {
-        unsafe { stdio::fclose(self.0); }
+impl<A: Allocator> Write for Array<u8, A> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.extend_from_slice(buf);
+        Ok(buf.len())
     }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The iterator returned by `BufRead::lines`
+pub struct Lines<B> {
+    reader: B
 }
 
+impl<B: BufRead> Iterator for Lines<B> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Result<String>> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => None,
+            Ok(_) => {
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+                Some(Ok(line))
+            }
+            Err(e) => Some(Err(e))
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{
+    alloc::Box,
+    container::Array
+};
+use super::{
+    BufRead, Error, ErrorKind, FileSeek, Read, Result, Seek, Write, DEFAULT_BUFFER_SIZE
+};
+use core::{
+    mem::forget,
+    ops::Drop,
+    ptr::read
+};
+
+/// Adds a buffer in front of a reader so that many small reads become a
+/// few large ones, and so that it can be used as a `BufRead`
+pub struct BufReader<R> {
+    inner: R,
+    buf: Box<[u8]>,
+    pos: usize,
+    filled: usize
+}
+
+/// Collects writes in a buffer and passes them on in large chunks. The
+/// buffer is flushed on drop, where errors are lost, so call `flush` when
+/// they matter
+pub struct BufWriter<W: Write> {
+    inner: W,
+    buf: Array<u8>
+}
+
+impl<R: Read> BufReader<R> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn new(inner: R) -> BufReader<R> {
+        BufReader::with_capacity(DEFAULT_BUFFER_SIZE, inner)
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn with_capacity(capacity: usize, inner: R) -> BufReader<R> {
+        let buf: Array<u8> = (0..capacity).map(|_| 0).collect();
+        BufReader {
+            inner: inner,
+            buf: Box::from_array(buf),
+            pos: 0,
+            filled: 0
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Reading from the inner reader directly skips whatever is buffered
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Bytes that have been read from the inner reader but not consumed
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.filled]
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns the inner reader. Buffered bytes are lost
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn discard_buffer(&mut self) {
+        self.pos = 0;
+        self.filled = 0;
+    }
+}
+
+impl<R: Read> Read for BufReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        // Large reads skip the buffer when it is empty
+        if self.pos == self.filled && buf.len() >= self.buf.len() {
+            self.discard_buffer();
+            return self.inner.read(buf);
+        }
+
+        let n = {
+            let mut available = self.fill_buf()?;
+            available.read(buf)?
+        };
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: Read> BufRead for BufReader<R> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        if self.pos == self.filled {
+            self.filled = self.inner.read(&mut self.buf)?;
+            self.pos = 0;
+        }
+        Ok(&self.buf[self.pos..self.filled])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.pos = (self.pos + amount).min(self.filled);
+    }
+}
+
+impl<R: Read + Seek> Seek for BufReader<R> {
+    /// Seeking drops the buffer. Offsets from `Cur` are taken from the
+    /// logical position, which trails the inner reader by the buffered
+    /// bytes
+    fn seek(&mut self, offset: i64, from: FileSeek) -> Result<u64> {
+        let result = match from {
+            FileSeek::Cur => {
+                let buffered = (self.filled - self.pos) as i64;
+                self.inner.seek(offset - buffered, FileSeek::Cur)
+            }
+            _ => self.inner.seek(offset, from)
+        };
+        self.discard_buffer();
+        result
+    }
+}
+
+impl<W: Write> BufWriter<W> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn new(inner: W) -> BufWriter<W> {
+        BufWriter::with_capacity(DEFAULT_BUFFER_SIZE, inner)
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn with_capacity(capacity: usize, inner: W) -> BufWriter<W> {
+        BufWriter { inner: inner, buf: Array::with_capacity(capacity) }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Writing to the inner writer directly skips ahead of whatever is
+    /// buffered
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Bytes waiting to be written
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Flushes the buffer and returns the inner writer, or hands the
+    /// `BufWriter` back if the buffer could not be written
+    pub fn into_inner(mut self) -> core::result::Result<W, BufWriter<W>> {
+        if self.flush_buf().is_err() {
+            return Err(self);
+        }
+
+        unsafe {
+            let inner = read(&self.inner);
+            drop(read(&self.buf));
+            forget(self);
+            Ok(inner)
+        }
+    }
+
+    /// Writes out the buffer, keeping whatever could not be written
+    fn flush_buf(&mut self) -> Result<()> {
+        let mut written = 0;
+        let mut result = Ok(());
+        while written < self.buf.len() {
+            match self.inner.write(&self.buf[written..]) {
+                Ok(0) => {
+                    result = Err(Error::new(ErrorKind::WriteZero));
+                    break;
+                }
+                Ok(n) => written += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        self.buf.drain(..written);
+        result
+    }
+}
+
+impl<W: Write> Write for BufWriter<W> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if self.buf.len() + buf.len() > self.buf.capacity() {
+            self.flush_buf()?;
+        }
+
+        // Writes that would not fit anyway go straight through
+        if buf.len() >= self.buf.capacity() {
+            self.inner.write(buf)
+        } else {
+            self.buf.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.flush_buf()?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write + Seek> Seek for BufWriter<W> {
+    /// Flushes the buffer before seeking
+    fn seek(&mut self, offset: i64, from: FileSeek) -> Result<u64> {
+        self.flush_buf()?;
+        self.inner.seek(offset, from)
+    }
+}
+
+impl<W: Write> Drop for BufWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.flush_buf();
+    }
+}
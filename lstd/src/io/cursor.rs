@@ -0,0 +1,142 @@
+use crate::{
+    alloc::Allocator,
+    container::Array
+};
+use super::{BufRead, Error, ErrorKind, FileSeek, Read, Result, Seek, Write};
+use core::convert::TryFrom;
+
+/// Gives an in-memory buffer a position so that it can be used with
+/// `Read`, `Write` and `Seek`. Writing to an `Array` grows it, writing to a
+/// slice stops at its end
+#[derive(Clone, Debug, Default)]
+pub struct Cursor<T> {
+    inner: T,
+    pos: u64
+}
+
+impl<T> Cursor<T> {
+    pub fn new(inner: T) -> Cursor<T> {
+        Cursor { inner: inner, pos: 0 }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// The position may be past the end of the buffer. Reads there return
+    /// nothing and writes to an `Array` pad the gap with zeros
+    pub fn set_position(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+}
+
+impl<T: AsRef<[u8]>> Cursor<T> {
+    /// The bytes from the position onwards
+    pub fn remaining(&self) -> &[u8] {
+        let bytes = self.inner.as_ref();
+        let start = (self.pos as usize).min(bytes.len());
+        &bytes[start..]
+    }
+}
+
+impl<T: AsRef<[u8]>> Read for Cursor<T> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.remaining().read(buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<T: AsRef<[u8]>> BufRead for Cursor<T> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        Ok(self.remaining())
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.pos += amount as u64;
+    }
+}
+
+impl<T: AsRef<[u8]>> Seek for Cursor<T> {
+    fn seek(&mut self, offset: i64, from: FileSeek) -> Result<u64> {
+        let base = match from {
+            FileSeek::Set => 0,
+            FileSeek::Cur => self.pos,
+            FileSeek::End => self.inner.as_ref().len() as u64
+        };
+        match base.checked_add_signed(offset) {
+            Some(pos) => {
+                self.pos = pos;
+                Ok(pos)
+            }
+            None => Err(Error::new(ErrorKind::InvalidInput))
+        }
+    }
+}
+
+impl Write for Cursor<&mut [u8]> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let start = (self.pos as usize).min(self.inner.len());
+        let n = (&mut self.inner[start..]).write(buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<A: Allocator> Write for Cursor<Array<u8, A>> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        write_to_array(&mut self.pos, &mut self.inner, buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<A: Allocator> Write for Cursor<&mut Array<u8, A>> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        write_to_array(&mut self.pos, self.inner, buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Overwrites from `pos` and grows the array past its end
+#[cfg_attr(feature = "track-allocations", track_caller)]
+fn write_to_array<A: Allocator>(pos: &mut u64, array: &mut Array<u8, A>, buf: &[u8]) -> Result<usize> {
+    let start = match usize::try_from(*pos) {
+        Ok(start) => start,
+        Err(_) => return Err(Error::new(ErrorKind::InvalidInput))
+    };
+
+    while array.len() < start {
+        array.push(0);
+    }
+
+    let overlap = (array.len() - start).min(buf.len());
+    array[start..start + overlap].copy_from_slice(&buf[..overlap]);
+    array.extend_from_slice(&buf[overlap..]);
+
+    *pos += buf.len() as u64;
+    Ok(buf.len())
+}
@@ -0,0 +1,374 @@
+use crate::{
+    container::Array,
+    ffi::CStr,
+    string::String
+};
+use super::{Error, ErrorKind, Read, Result, Seek, Write};
+use c::{
+    fcntl::*,
+    stat::{fstat, stat, mode_t, S_IFDIR, S_IFLNK, S_IFMT, S_IFREG},
+    stdio,
+    types::*,
+    unistd::close
+};
+use core::{
+    mem::MaybeUninit,
+    ops::Drop,
+    time::Duration
+};
+
+pub enum FileMode {
+    Read,
+    Write,
+    Append
+}
+
+/// Where `Seek::seek` measures its offset from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileSeek {
+    Set,
+    Cur,
+    End
+}
+
+pub struct File {
+    stream: *mut c_void,
+    last: LastOp
+}
+
+/// What the stream was last used for. C streams need a flush or a seek in
+/// between when switching from writing to reading or back
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LastOp {
+    None,
+    Read,
+    Write
+}
+
+unsafe impl Send for File {}
+
+/// Controls how `OpenOptions::open` opens a file, for the combinations
+/// `FileMode` doesn't cover
+///
+/// ```ignore
+/// let file = OpenOptions::new().read(true).write(true).create(true).open(path)?;
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OpenOptions {
+    read: bool,
+    write: bool,
+    append: bool,
+    truncate: bool,
+    create: bool,
+    create_new: bool
+}
+
+/// Size, type and modification time of a file
+#[derive(Clone, Copy, Debug)]
+pub struct Metadata {
+    len: u64,
+    mode: mode_t,
+    modified: Duration
+}
+
+impl File {
+    /// `Read` opens an existing file, `Write` creates or truncates one and
+    /// `Append` creates one or writes at its end
+    pub fn open(filename: &CStr, mode: FileMode) -> Result<File> {
+        let options = OpenOptions::new();
+        let options = match mode {
+            FileMode::Read   => options.read(true),
+            FileMode::Write  => options.write(true).create(true).truncate(true),
+            FileMode::Append => options.append(true).create(true)
+        };
+        options.open(filename)
+    }
+
+    /// The descriptor under the stream. It stays owned by the `File`
+    pub fn as_raw_fd(&self) -> c_int {
+        unsafe { stdio::fileno(self.stream) }
+    }
+
+    pub fn metadata(&self) -> Result<Metadata> {
+        let mut buf = MaybeUninit::<stat>::uninit();
+        unsafe {
+            if fstat(self.as_raw_fd(), buf.as_mut_ptr()) != 0 {
+                return Err(Error::last_os_error());
+            }
+            Ok(Metadata::from_stat(&buf.assume_init()))
+        }
+    }
+
+    /// Takes ownership of an open descriptor. `mode` is the NUL-terminated
+    /// `fdopen` mode, which must match how the descriptor was opened
+    pub(crate) unsafe fn from_fd(fd: c_int, mode: &[u8]) -> Result<File> {
+        let stream = stdio::fdopen(fd, mode.as_ptr() as *const c_char);
+        if stream.is_null() {
+            let error = Error::last_os_error();
+            close(fd);
+            return Err(error);
+        }
+
+        Ok(File { stream: stream, last: LastOp::None })
+    }
+
+    /// Takes the error flag off the stream and returns the error behind it
+    fn take_error(&self) -> Error {
+        let error = Error::last_os_error();
+        unsafe { stdio::clearerr(self.stream); }
+        error
+    }
+
+    /// Gets the stream ready for `next`. Written data is flushed before a
+    /// read, and seeking to where the stream already is drops read-ahead
+    /// before a write
+    fn switch_to(&mut self, next: LastOp) -> Result<()> {
+        let failed = match (self.last, next) {
+            (LastOp::Write, LastOp::Read) => unsafe { stdio::fflush(self.stream) != 0 },
+            (LastOp::Read, LastOp::Write) => unsafe {
+                stdio::fseeko(self.stream, 0, stdio::SEEK_CUR) != 0
+            },
+            _ => false
+        };
+        if failed {
+            return Err(self.take_error());
+        }
+        self.last = next;
+        Ok(())
+    }
+}
+
+impl OpenOptions {
+    /// Every option starts out off
+    pub fn new() -> OpenOptions {
+        OpenOptions::default()
+    }
+
+    pub fn read(mut self, read: bool) -> OpenOptions {
+        self.read = read;
+        self
+    }
+
+    pub fn write(mut self, write: bool) -> OpenOptions {
+        self.write = write;
+        self
+    }
+
+    /// Every write goes to the end of the file. Implies `write`
+    pub fn append(mut self, append: bool) -> OpenOptions {
+        self.append = append;
+        self
+    }
+
+    /// Cuts an existing file down to nothing. Needs `write`
+    pub fn truncate(mut self, truncate: bool) -> OpenOptions {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Creates the file if it doesn't exist
+    pub fn create(mut self, create: bool) -> OpenOptions {
+        self.create = create;
+        self
+    }
+
+    /// Creates the file and fails with `AlreadyExists` if it is already
+    /// there
+    pub fn create_new(mut self, create_new: bool) -> OpenOptions {
+        self.create_new = create_new;
+        self
+    }
+
+    pub fn open(&self, filename: &CStr) -> Result<File> {
+        let writes = self.write || self.append;
+        if !self.read && !writes {
+            return Err(Error::new(ErrorKind::InvalidInput));
+        }
+        if !writes && (self.truncate || self.create || self.create_new) {
+            return Err(Error::new(ErrorKind::InvalidInput));
+        }
+        if self.append && self.truncate {
+            return Err(Error::new(ErrorKind::InvalidInput));
+        }
+
+        // `fdopen` doesn't create or truncate, so its mode only has to
+        // agree with the access flags
+        let (mut flags, mode): (c_int, &[u8]) = match (self.read, writes, self.append) {
+            (true, false, _)     => (O_RDONLY, b"r\0"),
+            (false, true, false) => (O_WRONLY, b"w\0"),
+            (true, true, false)  => (O_RDWR, b"r+\0"),
+            (false, true, true)  => (O_WRONLY, b"a\0"),
+            (true, true, true)   => (O_RDWR, b"a+\0"),
+            (false, false, _)    => unreachable!()
+        };
+        if self.append { flags |= O_APPEND; }
+        if self.truncate { flags |= O_TRUNC; }
+        if self.create { flags |= O_CREAT; }
+        if self.create_new { flags |= O_CREAT | O_EXCL; }
+
+        unsafe {
+            let fd = open(filename.as_ptr(), flags | O_CLOEXEC, 0o666 as c_uint);
+            if fd < 0 {
+                return Err(Error::last_os_error());
+            }
+            File::from_fd(fd, mode)
+        }
+    }
+}
+
+impl Metadata {
+    pub(crate) fn from_stat(buf: &stat) -> Metadata {
+        Metadata {
+            len: buf.st_size as u64,
+            mode: buf.st_mode,
+            modified: Duration::new(buf.st_mtime as u64, buf.st_mtime_nsec as u32)
+        }
+    }
+
+    /// Size in bytes
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.mode & S_IFMT == S_IFREG
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.mode & S_IFMT == S_IFDIR
+    }
+
+    /// Only ever true for metadata that was looked up without following
+    /// symlinks
+    pub fn is_symlink(&self) -> bool {
+        self.mode & S_IFMT == S_IFLNK
+    }
+
+    /// Last modification time, since the Unix epoch
+    pub fn modified(&self) -> Duration {
+        self.modified
+    }
+}
+
+/// Looks up the metadata of the file at `filename`, following symlinks
+pub fn metadata(filename: &CStr) -> Result<Metadata> {
+    let mut buf = MaybeUninit::<stat>::uninit();
+    unsafe {
+        if stat(filename.as_ptr(), buf.as_mut_ptr()) != 0 {
+            return Err(Error::last_os_error());
+        }
+        Ok(Metadata::from_stat(&buf.assume_init()))
+    }
+}
+
+/// Reads a whole file into memory
+#[cfg_attr(feature = "track-allocations", track_caller)]
+pub fn read_file(filename: &CStr) -> Result<Array<u8>> {
+    let mut file = File::open(filename, FileMode::Read)?;
+    let len = file.metadata().map_or(0, |m| m.len() as usize);
+
+    let mut bytes = Array::with_capacity(len);
+    file.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Reads a whole file into memory, failing with `InvalidData` if it is
+/// not UTF-8
+#[cfg_attr(feature = "track-allocations", track_caller)]
+pub fn read_file_to_string(filename: &CStr) -> Result<String> {
+    String::from_utf8(read_file(filename)?)
+        .map_err(|_| Error::new(ErrorKind::InvalidData))
+}
+
+/// Creates or truncates the file at `filename` and writes `bytes` to it
+pub fn write_file(filename: &CStr, bytes: &[u8]) -> Result<()> {
+    let mut file = File::open(filename, FileMode::Write)?;
+    file.write_all(bytes)?;
+    file.flush()
+}
+
+impl Read for File {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.switch_to(LastOp::Read)?;
+        let read = unsafe {
+            stdio::fread(
+                buf.as_mut_ptr() as *mut _,
+                1,
+                buf.len(),
+                self.stream
+            )
+        };
+
+        if read < buf.len() && unsafe { stdio::ferror(self.stream) } != 0 {
+            Err(self.take_error())
+        } else {
+            Ok(read)
+        }
+    }
+}
+
+impl Write for File {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.switch_to(LastOp::Write)?;
+        let written = unsafe {
+            stdio::fwrite(
+                buf.as_ptr() as *mut _,
+                1,
+                buf.len(),
+                self.stream
+            )
+        };
+
+        if written < buf.len() && unsafe { stdio::ferror(self.stream) } != 0 {
+            Err(self.take_error())
+        } else {
+            Ok(written)
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if unsafe { stdio::fflush(self.stream) } != 0 {
+            return Err(self.take_error());
+        }
+        if self.last == LastOp::Write {
+            self.last = LastOp::None;
+        }
+        Ok(())
+    }
+}
+
+impl Seek for File {
+    fn seek(&mut self, offset: i64, from: FileSeek) -> Result<u64> {
+        let whence = match from {
+            FileSeek::Set => stdio::SEEK_SET,
+            FileSeek::Cur => stdio::SEEK_CUR,
+            FileSeek::End => stdio::SEEK_END
+        };
+
+        if unsafe { stdio::fseeko(self.stream, offset, whence) } != 0 {
+            return Err(Error::last_os_error());
+        }
+        // Either direction is fine after a seek
+        self.last = LastOp::None;
+        self.tell()
+    }
+
+    fn tell(&mut self) -> Result<u64> {
+        let pos = unsafe { stdio::ftello(self.stream) };
+        if pos < 0 {
+            Err(Error::last_os_error())
+        } else {
+            Ok(pos as u64)
+        }
+    }
+}
+
+impl Drop for File {
+    fn drop(&mut self) {
+        unsafe { stdio::fclose(self.stream); }
+    }
+}
@@ -0,0 +1,314 @@
+use crate::ffi::CStr;
+use super::{Error, ErrorKind, File, FileMode, Result};
+use c::{
+    mman::*,
+    types::*,
+    unistd::{sysconf, _SC_PAGESIZE}
+};
+use core::{
+    convert::TryFrom,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    ops::{Deref, DerefMut, Drop},
+    ptr::{null_mut, NonNull},
+    slice::{from_raw_parts, from_raw_parts_mut}
+};
+
+/// How mapped pages are going to be accessed, passed on to `madvise` so
+/// the kernel can tune readahead
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Advice {
+    Normal,
+    Random,
+    Sequential,
+    /// Start reading the pages in now
+    WillNeed
+}
+
+/// A read-only mapping of a file. The mapping stays valid after the
+/// `File` is closed, but its contents change if the file is modified, and
+/// reading past a point the file has been truncated to is fatal
+pub struct Mmap {
+    map: RawMap
+}
+
+/// A writable mapping. Mappings of a file are shared, so writes reach
+/// the file, and `flush` waits for them to do so
+pub struct MmapMut {
+    map: RawMap
+}
+
+/// The pages behind a mapping and the part of them that was asked for,
+/// which starts partway into the first page when the offset isn't page
+/// aligned
+struct RawMap {
+    base: *mut c_void,
+    base_len: usize,
+    ptr: *mut u8,
+    len: usize
+}
+
+unsafe impl Send for RawMap {}
+unsafe impl Sync for RawMap {}
+
+impl RawMap {
+    fn new(fd: c_int, offset: u64, len: usize, prot: c_int, flags: c_int) -> Result<RawMap> {
+        if len == 0 {
+            // mmap refuses empty mappings
+            return Ok(RawMap {
+                base: null_mut(),
+                base_len: 0,
+                ptr: NonNull::dangling().as_ptr(),
+                len: 0
+            });
+        }
+
+        let page = unsafe { sysconf(_SC_PAGESIZE) } as u64;
+        let delta = (offset % page) as usize;
+        let base_len = match len.checked_add(delta) {
+            Some(base_len) => base_len,
+            None => return Err(Error::new(ErrorKind::InvalidInput))
+        };
+
+        let base = unsafe {
+            mmap(null_mut(), base_len, prot, flags, fd, (offset - delta as u64) as c_long)
+        };
+        if base == MAP_FAILED {
+            return Err(Error::last_os_error());
+        }
+
+        Ok(RawMap {
+            base: base,
+            base_len: base_len,
+            ptr: unsafe { (base as *mut u8).add(delta) },
+            len: len
+        })
+    }
+
+    fn file(file: &File, offset: u64, len: Option<usize>, prot: c_int) -> Result<RawMap> {
+        let file_len = file.metadata()?.len();
+        let len = match len {
+            Some(len) => len,
+            None => match usize::try_from(file_len.saturating_sub(offset)) {
+                Ok(len) => len,
+                Err(_) => return Err(Error::new(ErrorKind::InvalidInput))
+            }
+        };
+
+        // Pages past the end of the file raise SIGBUS when touched
+        match offset.checked_add(len as u64) {
+            Some(end) if end <= file_len => {}
+            _ => return Err(Error::new(ErrorKind::InvalidInput))
+        }
+
+        RawMap::new(file.as_raw_fd(), offset, len, prot, MAP_SHARED)
+    }
+
+    fn advise(&self, advice: Advice) -> Result<()> {
+        self.madvise(match advice {
+            Advice::Normal     => MADV_NORMAL,
+            Advice::Random     => MADV_RANDOM,
+            Advice::Sequential => MADV_SEQUENTIAL,
+            Advice::WillNeed   => MADV_WILLNEED
+        })
+    }
+
+    fn madvise(&self, advice: c_int) -> Result<()> {
+        if self.len == 0 { return Ok(()); }
+
+        if unsafe { madvise(self.base, self.base_len, advice) } != 0 {
+            return Err(Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn protect(&self, prot: c_int) -> Result<()> {
+        if self.len == 0 { return Ok(()); }
+
+        if unsafe { mprotect(self.base, self.base_len, prot) } != 0 {
+            return Err(Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn sync(&self, flags: c_int) -> Result<()> {
+        if self.len == 0 { return Ok(()); }
+
+        if unsafe { msync(self.base, self.base_len, flags) } != 0 {
+            return Err(Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RawMap {
+    fn drop(&mut self) {
+        if self.len != 0 {
+            unsafe { munmap(self.base, self.base_len); }
+        }
+    }
+}
+
+impl Mmap {
+    /// Maps the whole file. Writes still sitting in the `File`'s buffer
+    /// are not visible, so flush it first
+    ///
+    /// # Safety
+    ///
+    /// The file must not be written to or truncated, by this process or
+    /// any other, while the mapping is alive. Writes would change memory
+    /// behind a `&[u8]`, and touching pages past a new end raises SIGBUS
+    pub unsafe fn map(file: &File) -> Result<Mmap> {
+        Ok(Mmap { map: RawMap::file(file, 0, None, PROT_READ)? })
+    }
+
+    /// Maps `len` bytes from `offset`, which doesn't need to be page
+    /// aligned. The range must lie within the file
+    ///
+    /// # Safety
+    ///
+    /// The same as `map`
+    pub unsafe fn map_range(file: &File, offset: u64, len: usize) -> Result<Mmap> {
+        Ok(Mmap { map: RawMap::file(file, offset, Some(len), PROT_READ)? })
+    }
+
+    /// Opens and maps a whole file
+    ///
+    /// # Safety
+    ///
+    /// The same as `map`
+    pub unsafe fn open(filename: &CStr) -> Result<Mmap> {
+        Mmap::map(&File::open(filename, FileMode::Read)?)
+    }
+
+    pub fn advise(&self, advice: Advice) -> Result<()> {
+        self.map.advise(advice)
+    }
+
+    /// Lets the kernel drop the pages for now. Touching them again faults
+    /// them back in from the file, or as zeros if this mapping started out
+    /// as `MmapMut::map_anon`, which is why nothing may borrow it meanwhile
+    pub fn dont_need(&mut self) -> Result<()> {
+        self.map.madvise(MADV_DONTNEED)
+    }
+
+    /// Makes the pages writable. Only works on mappings that came from
+    /// `MmapMut::make_read_only` or of files opened for writing
+    pub fn make_mut(self) -> Result<MmapMut> {
+        self.map.protect(PROT_READ | PROT_WRITE)?;
+        Ok(MmapMut { map: self.map })
+    }
+}
+
+impl MmapMut {
+    /// Maps the whole file for reading and writing. The file must have
+    /// been opened for both
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated other than through this
+    /// mapping while it is alive, by this process or any other. Other
+    /// writes would change memory behind a `&mut [u8]`, and touching pages
+    /// past a new end raises SIGBUS
+    pub unsafe fn map_mut(file: &File) -> Result<MmapMut> {
+        Ok(MmapMut { map: RawMap::file(file, 0, None, PROT_READ | PROT_WRITE)? })
+    }
+
+    /// Maps `len` bytes from `offset` for reading and writing
+    ///
+    /// # Safety
+    ///
+    /// The same as `map_mut`
+    pub unsafe fn map_range_mut(file: &File, offset: u64, len: usize) -> Result<MmapMut> {
+        Ok(MmapMut { map: RawMap::file(file, offset, Some(len), PROT_READ | PROT_WRITE)? })
+    }
+
+    /// Zeroed memory that isn't backed by any file
+    pub fn map_anon(len: usize) -> Result<MmapMut> {
+        let map = RawMap::new(-1, 0, len, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS)?;
+        Ok(MmapMut { map: map })
+    }
+
+    /// Writes modified pages back to the file and waits for them
+    pub fn flush(&self) -> Result<()> {
+        self.map.sync(MS_SYNC)
+    }
+
+    /// Starts writing modified pages back without waiting
+    pub fn flush_async(&self) -> Result<()> {
+        self.map.sync(MS_ASYNC)
+    }
+
+    pub fn advise(&self, advice: Advice) -> Result<()> {
+        self.map.advise(advice)
+    }
+
+    /// Lets the kernel drop the pages for now. File pages keep their
+    /// contents, but anonymous memory comes back zeroed, so this needs
+    /// the mapping to not be borrowed
+    pub fn dont_need(&mut self) -> Result<()> {
+        self.map.madvise(MADV_DONTNEED)
+    }
+
+    pub fn make_read_only(self) -> Result<Mmap> {
+        self.map.protect(PROT_READ)?;
+        Ok(Mmap { map: self.map })
+    }
+}
+
+impl Deref for Mmap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { from_raw_parts(self.map.ptr, self.map.len) }
+    }
+}
+
+impl AsRef<[u8]> for Mmap {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Deref for MmapMut {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { from_raw_parts(self.map.ptr, self.map.len) }
+    }
+}
+
+impl DerefMut for MmapMut {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { from_raw_parts_mut(self.map.ptr, self.map.len) }
+    }
+}
+
+impl AsRef<[u8]> for MmapMut {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl AsMut<[u8]> for MmapMut {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl Debug for Mmap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Mmap")
+            .field("ptr", &self.map.ptr)
+            .field("len", &self.map.len)
+            .finish()
+    }
+}
+
+impl Debug for MmapMut {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("MmapMut")
+            .field("ptr", &self.map.ptr)
+            .field("len", &self.map.len)
+            .finish()
+    }
+}
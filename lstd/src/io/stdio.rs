@@ -0,0 +1,253 @@
+use crate::sync::{Mutex, MutexGuard, Once};
+use super::{Error, Read, Result, Write};
+use c::{
+    stdio,
+    stdlib::atexit,
+    types::*,
+    unistd
+};
+use core::{
+    fmt::Arguments,
+    ops::Drop
+};
+
+const BUFFER_SIZE: usize = 4096;
+
+/// Output waiting to be written to a file descriptor. It lives in a static
+/// array rather than on the heap so that printing never allocates, which
+/// keeps it usable from allocator hooks
+struct Buffer {
+    fd: c_int,
+    bytes: [u8; BUFFER_SIZE],
+    len: usize
+}
+
+impl Buffer {
+    const fn new(fd: c_int) -> Buffer {
+        Buffer { fd: fd, bytes: [0; BUFFER_SIZE], len: 0 }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        if bytes.len() > BUFFER_SIZE - self.len {
+            self.flush();
+        }
+
+        if bytes.len() >= BUFFER_SIZE {
+            write_fd(self.fd, bytes);
+        } else {
+            self.bytes[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+            self.len += bytes.len();
+        }
+    }
+
+    fn flush(&mut self) {
+        write_fd(self.fd, &self.bytes[..self.len]);
+        self.len = 0;
+    }
+}
+
+/// Writes all of `bytes`, retrying short writes. Errors are dropped since
+/// there is nowhere left to report them
+fn write_fd(fd: c_int, mut bytes: &[u8]) {
+    while !bytes.is_empty() {
+        let written = unsafe {
+            stdio::write(fd, bytes.as_ptr() as *const _, bytes.len())
+        };
+        if written <= 0 { return; }
+        bytes = &bytes[written as usize..];
+    }
+}
+
+static STDOUT_BUFFER: Mutex<Buffer> = Mutex::new(Buffer::new(stdio::STDOUT));
+static STDERR_BUFFER: Mutex<Buffer> = Mutex::new(Buffer::new(stdio::STDERR));
+static FLUSH_AT_EXIT: Once = Once::new();
+
+extern "C" fn flush_stdout() {
+    STDOUT_BUFFER.lock().flush();
+}
+
+/// Handle to the process's standard input. Reads go straight to the file
+/// descriptor, so wrap it in a `BufReader` to read lines
+pub struct Stdin;
+
+/// Handle to the process's standard output. Output is line buffered and
+/// whatever is left over is flushed at exit
+pub struct Stdout;
+
+/// Exclusive access to stdout. Everything written through one lock comes
+/// out together, even when other threads are printing. Printing from a
+/// `Display` impl while holding the lock on the same thread deadlocks
+pub struct StdoutLock<'a> {
+    buffer: MutexGuard<'a, Buffer>
+}
+
+/// Handle to the process's standard error. Each lock is flushed as soon
+/// as it is released
+pub struct Stderr;
+
+pub struct StderrLock<'a> {
+    buffer: MutexGuard<'a, Buffer>
+}
+
+pub fn stdin() -> Stdin {
+    Stdin
+}
+
+pub fn stdout() -> Stdout {
+    Stdout
+}
+
+pub fn stderr() -> Stderr {
+    Stderr
+}
+
+impl Read for Stdin {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let read = unsafe {
+            unistd::read(stdio::STDIN, buf.as_mut_ptr() as *mut _, buf.len())
+        };
+        if read < 0 {
+            Err(Error::last_os_error())
+        } else {
+            Ok(read as usize)
+        }
+    }
+}
+
+impl Stdout {
+    pub fn lock(&self) -> StdoutLock<'static> {
+        FLUSH_AT_EXIT.call_once(|| unsafe { atexit(flush_stdout); });
+        StdoutLock { buffer: STDOUT_BUFFER.lock() }
+    }
+}
+
+impl Write for Stdout {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.lock().write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.lock().flush()
+    }
+
+    fn write_fmt(&mut self, args: Arguments<'_>) -> Result<()> {
+        self.lock().write_fmt(args)
+    }
+}
+
+impl<'a> Write for StdoutLock<'a> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.buffer.write(buf);
+        if buf.contains(&b'\n') {
+            self.buffer.flush();
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.buffer.flush();
+        Ok(())
+    }
+}
+
+impl Stderr {
+    pub fn lock(&self) -> StderrLock<'static> {
+        StderrLock { buffer: STDERR_BUFFER.lock() }
+    }
+}
+
+impl Write for Stderr {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.lock().write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn write_fmt(&mut self, args: Arguments<'_>) -> Result<()> {
+        self.lock().write_fmt(args)
+    }
+}
+
+impl<'a> Write for StderrLock<'a> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.buffer.write(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.buffer.flush();
+        Ok(())
+    }
+}
+
+impl<'a> Drop for StderrLock<'a> {
+    fn drop(&mut self) {
+        self.buffer.flush();
+    }
+}
+
+pub fn print(s: &str) {
+    let _ = stdout().lock().write_all(s.as_bytes());
+}
+
+#[doc(hidden)]
+pub fn _print(args: Arguments<'_>) {
+    let _ = stdout().lock().write_fmt(args);
+}
+
+#[doc(hidden)]
+pub fn _println(args: Arguments<'_>) {
+    let mut out = stdout().lock();
+    let _ = out.write_fmt(args);
+    let _ = out.write_all(b"\n");
+}
+
+#[doc(hidden)]
+pub fn _eprint(args: Arguments<'_>) {
+    let _ = stderr().lock().write_fmt(args);
+}
+
+#[doc(hidden)]
+pub fn _eprintln(args: Arguments<'_>) {
+    let mut err = stderr().lock();
+    let _ = err.write_fmt(args);
+    let _ = err.write_all(b"\n");
+}
+
+/// Prints to stdout. `write!` and `writeln!` work on `stdout().lock()`,
+/// `String` and any other `io::Write` or `fmt::Write` type
+#[macro_export]
+macro_rules! print {
+    ($($arg:tt)*) => {
+        $crate::io::_print(format_args!($($arg)*))
+    }
+}
+
+#[macro_export]
+macro_rules! println {
+    () => {
+        $crate::io::print("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::io::_println(format_args!($($arg)*))
+    }
+}
+
+#[macro_export]
+macro_rules! eprint {
+    ($($arg:tt)*) => {
+        $crate::io::_eprint(format_args!($($arg)*))
+    }
+}
+
+#[macro_export]
+macro_rules! eprintln {
+    () => {
+        $crate::io::_eprint(format_args!("\n"))
+    };
+    ($($arg:tt)*) => {
+        $crate::io::_eprintln(format_args!($($arg)*))
+    }
+}
@@ -0,0 +1,341 @@
+use crate::{
+    alloc::Box,
+    container::{Array, Deque},
+    sync::{Arc, Condvar, Mutex},
+    thread::{self, JoinHandle},
+    thread_local
+};
+use c::unistd::{sysconf, _SC_NPROCESSORS_ONLN};
+use core::{
+    cell::Cell,
+    mem::{forget, take},
+    ops::Drop,
+    ptr::read,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering}
+};
+
+/// A type-erased `FnOnce` waiting to run. The closure is boxed and `call`
+/// and `drop` know its real type
+struct Job {
+    call: unsafe fn(*mut u8),
+    drop: unsafe fn(*mut u8),
+    data: *mut u8,
+    counter: Option<Counter>
+}
+
+unsafe impl Send for Job {}
+
+impl Job {
+    fn new<F: FnOnce() + Send + 'static>(f: F, counter: Option<Counter>) -> Job {
+        unsafe fn call<F: FnOnce()>(data: *mut u8) {
+            let f = Box::into_inner(Box::from_raw(data as *mut F));
+            f();
+        }
+
+        unsafe fn drop<F>(data: *mut u8) {
+            core::mem::drop(Box::from_raw(data as *mut F));
+        }
+
+        Job {
+            call: call::<F>,
+            drop: drop::<F>,
+            data: Box::into_raw(Box::new(f)) as *mut u8,
+            counter: counter
+        }
+    }
+
+    /// Runs the job. If it was the last one its counter was waiting for,
+    /// returns the jobs that were held back until the counter reached zero
+    fn run(self) -> Option<Array<Job>> {
+        let call = self.call;
+        let data = self.data;
+        let counter = unsafe { read(&self.counter) };
+        forget(self);
+
+        unsafe { call(data); }
+        counter.and_then(|c| c.decrement())
+    }
+}
+
+impl Drop for Job {
+    /// Only jobs that never became runnable get here, such as those still
+    /// waiting on a counter when it is dropped
+    fn drop(&mut self) {
+        unsafe { (self.drop)(self.data); }
+    }
+}
+
+struct CounterInner {
+    count: AtomicUsize,
+    waiting: Mutex<Array<Job>>
+}
+
+/// Tracks a group of jobs. Jobs spawned with a counter raise it and lower
+/// it again when they finish, so `JobSystem::wait` can block on the whole
+/// group, and `spawn_after` can hold jobs back until the group is done.
+/// A counter's jobs should all go to the same `JobSystem`, since that is
+/// the one that wakes its waiters
+#[derive(Clone)]
+pub struct Counter {
+    inner: Arc<CounterInner>
+}
+
+impl Counter {
+    pub fn new() -> Counter {
+        Counter {
+            inner: Arc::new(CounterInner {
+                count: AtomicUsize::new(0),
+                waiting: Mutex::new(Array::new())
+            })
+        }
+    }
+
+    /// Number of jobs in the group that have not finished yet
+    pub fn get(&self) -> usize {
+        self.inner.count.load(Ordering::Acquire)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.get() == 0
+    }
+
+    fn increment(&self) {
+        self.inner.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the jobs held back by `spawn_after`, possibly none, once
+    /// the count reaches zero
+    fn decrement(&self) -> Option<Array<Job>> {
+        if self.inner.count.fetch_sub(1, Ordering::AcqRel) != 1 { return None; }
+
+        Some(take(&mut *self.inner.waiting.lock()))
+    }
+}
+
+impl Default for Counter {
+    fn default() -> Counter {
+        Counter::new()
+    }
+}
+
+struct Shared {
+    queues: Array<Mutex<Deque<Job>>>,
+    pending: AtomicUsize,
+    next_queue: AtomicUsize,
+    shutdown: AtomicBool,
+    sleep: Mutex<()>,
+    wake: Condvar
+}
+
+thread_local! {
+    // The queue owned by the current thread, if it is a worker
+    static WORKER: Cell<Option<(usize, usize)>> = Cell::new(None);
+}
+
+impl Shared {
+    fn id(&self) -> usize {
+        self as *const Shared as usize
+    }
+
+    /// Workers push onto their own queue so that related jobs stay on one
+    /// thread; everyone else spreads jobs round robin
+    fn push(&self, job: Job) {
+        let queue = match WORKER.with(|w| w.get()) {
+            Some((id, index)) if id == self.id() => index,
+            _ => self.next_queue.fetch_add(1, Ordering::Relaxed) % self.queues.len()
+        };
+
+        // Counted first, so a thread that steals the job straight away can't
+        // take `pending` below zero
+        self.pending.fetch_add(1, Ordering::Release);
+        self.queues[queue].lock().push_back(job);
+
+        // Taking the lock orders this with a worker that is about to sleep,
+        // so it either sees the job or gets the notification
+        drop(self.sleep.lock());
+        self.wake.notify_one();
+    }
+
+    /// A worker takes the newest job from its own queue, which is likely
+    /// still warm in its cache, and otherwise steals the oldest job from
+    /// someone else
+    fn find_job(&self, own: Option<usize>) -> Option<Job> {
+        if self.pending.load(Ordering::Acquire) == 0 { return None; }
+
+        if let Some(index) = own {
+            if let Some(job) = self.queues[index].lock().pop_back() {
+                self.pending.fetch_sub(1, Ordering::AcqRel);
+                return Some(job);
+            }
+        }
+
+        let start = own.map_or(0, |i| i + 1);
+        for i in 0..self.queues.len() {
+            let victim = (start + i) % self.queues.len();
+            if Some(victim) == own { continue; }
+
+            if let Some(job) = self.queues[victim].lock().pop_front() {
+                self.pending.fetch_sub(1, Ordering::AcqRel);
+                return Some(job);
+            }
+        }
+
+        None
+    }
+
+    fn execute(&self, job: Job) {
+        if let Some(ready) = job.run() {
+            for job in ready {
+                self.push(job);
+            }
+
+            // The counter reached zero, which threads blocked in `wait`
+            // are sleeping on
+            drop(self.sleep.lock());
+            self.wake.notify_all();
+        }
+    }
+}
+
+fn worker_main(shared: Arc<Shared>, index: usize) {
+    WORKER.with(|w| w.set(Some((shared.id(), index))));
+
+    loop {
+        if let Some(job) = shared.find_job(Some(index)) {
+            shared.execute(job);
+            continue;
+        }
+
+        let guard = shared.sleep.lock();
+        let _guard = shared.wake.wait_while(guard, |_| {
+            shared.pending.load(Ordering::Acquire) == 0
+                && !shared.shutdown.load(Ordering::Acquire)
+        });
+
+        if shared.shutdown.load(Ordering::Acquire)
+            && shared.pending.load(Ordering::Acquire) == 0
+        {
+            return;
+        }
+    }
+}
+
+/// A fixed pool of worker threads that run jobs from per-worker queues,
+/// stealing from each other when their own queue runs dry. Dropping the
+/// pool finishes every queued job before the workers exit
+pub struct JobSystem {
+    shared: Arc<Shared>,
+    workers: Array<JoinHandle<()>>
+}
+
+impl JobSystem {
+    pub fn new(workers: usize) -> JobSystem {
+        let workers = if workers == 0 { 1 } else { workers };
+
+        let shared = Arc::new(Shared {
+            queues: (0..workers).map(|_| Mutex::new(Deque::new())).collect(),
+            pending: AtomicUsize::new(0),
+            next_queue: AtomicUsize::new(0),
+            shutdown: AtomicBool::new(false),
+            sleep: Mutex::new(()),
+            wake: Condvar::new()
+        });
+
+        let handles = (0..workers)
+            .map(|index| {
+                let shared = shared.clone();
+                let handle = thread::Builder::new()
+                    .name("lstd-job")
+                    .spawn(move || worker_main(shared, index));
+                match handle {
+                    Some(handle) => handle,
+                    None => crate::abort()
+                }
+            })
+            .collect();
+
+        JobSystem { shared: shared, workers: handles }
+    }
+
+    /// One worker per online CPU, leaving one for the thread that feeds
+    /// the pool
+    pub fn with_default_workers() -> JobSystem {
+        let cpus = unsafe { sysconf(_SC_NPROCESSORS_ONLN) };
+        let workers = if cpus > 1 { cpus as usize - 1 } else { 1 };
+        JobSystem::new(workers)
+    }
+
+    pub fn worker_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn spawn<F: FnOnce() + Send + 'static>(&self, f: F) {
+        self.shared.push(Job::new(f, None));
+    }
+
+    /// Spawns a job as part of the group tracked by `counter`
+    pub fn spawn_counted<F: FnOnce() + Send + 'static>(&self, counter: &Counter, f: F) {
+        counter.increment();
+        self.shared.push(Job::new(f, Some(counter.clone())));
+    }
+
+    /// Spawns a job that only becomes runnable once every job counted by
+    /// `dependency` has finished. It joins the `counter` group right away,
+    /// so waiting on that counter also waits for the dependency
+    pub fn spawn_after<F: FnOnce() + Send + 'static>(
+        &self,
+        dependency: &Counter,
+        counter: Option<&Counter>,
+        f: F
+    ) {
+        if let Some(counter) = counter {
+            counter.increment();
+        }
+        let job = Job::new(f, counter.cloned());
+
+        let mut waiting = dependency.inner.waiting.lock();
+        if dependency.is_done() {
+            drop(waiting);
+            self.shared.push(job);
+        } else {
+            waiting.push(job);
+        }
+    }
+
+    /// Blocks until every job counted by `counter` has finished. The
+    /// calling thread runs queued jobs in the meantime rather than sitting
+    /// idle, so waiting from inside a job can't starve the pool, and sleeps
+    /// alongside the workers once there are none left
+    pub fn wait(&self, counter: &Counter) {
+        let shared = &*self.shared;
+        let own = WORKER.with(|w| w.get())
+            .filter(|&(id, _)| id == shared.id())
+            .map(|(_, index)| index);
+
+        while !counter.is_done() {
+            if let Some(job) = shared.find_job(own) {
+                shared.execute(job);
+                continue;
+            }
+
+            // New jobs and the counter reaching zero both notify under the
+            // lock, so neither can slip in between the check and the sleep
+            let guard = shared.sleep.lock();
+            let _guard = shared.wake.wait_while(guard, |_| {
+                !counter.is_done() && shared.pending.load(Ordering::Acquire) == 0
+            });
+        }
+    }
+}
+
+impl Drop for JobSystem {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::Release);
+        drop(self.shared.sleep.lock());
+        self.shared.wake.notify_all();
+
+        for worker in take(&mut self.workers) {
+            worker.join();
+        }
+    }
+}
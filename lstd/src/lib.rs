@@ -1,26 +1,58 @@
 #![no_std]
+// Fields are spelled out in struct literals throughout the crate
+#![allow(clippy::redundant_field_names)]
 
 use c::stdlib;
 
 pub mod io;
 pub mod alloc;
+pub mod backtrace;
 pub mod container;
+pub mod crash;
+pub mod env;
+pub mod error;
+pub mod ffi;
+pub mod fs;
+pub mod job;
+pub mod log;
 pub mod string;
+pub mod sync;
+pub mod thread;
+pub mod time;
 
 pub fn abort() -> ! {
     unsafe { stdlib::abort() }
 }
 
 pub mod prelude {
-    pub use crate::{print, println};
+    pub use crate::{eprint, eprintln, format, print, println};
     pub use crate::alloc::Box;
     pub use crate::container::{Array, Rc};
+    pub use crate::string::String;
+    pub use crate::sync::Arc;
     pub use crate::cstr;
+    pub use crate::ffi::{CStr, CString};
 }
 
+/// Turns a string literal into a `&'static CStr`. A literal with a NUL in
+/// it fails to compile
 #[macro_export]
 macro_rules! cstr {
     ($string:literal) => {{
-        concat!($string, "\0").as_ptr() as *const c_char
+        const CSTR: &$crate::ffi::CStr = match $crate::ffi::CStr::from_bytes_with_nul(
+            concat!($string, "\0").as_bytes()
+        ) {
+            Ok(s) => s,
+            Err(_) => panic!("string literal contains a NUL byte")
+        };
+        CSTR
     }}
 }
+
+/// Formats into a new `String`
+#[macro_export]
+macro_rules! format {
+    ($($arg:tt)*) => {
+        $crate::string::format(format_args!($($arg)*))
+    }
+}
@@ -0,0 +1,401 @@
+use crate::{
+    alloc::Box,
+    container::Array,
+    into_dyn,
+    io::{stderr, Write},
+    string::String,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH}
+};
+use core::{
+    cmp::Ordering,
+    fmt::{Arguments, Display, Formatter, Result as FmtResult},
+    sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+    time::Duration
+};
+
+mod file;
+mod ring;
+pub use file::*;
+pub use ring::*;
+
+/// How important a record is. `Error` is the most severe and `Trace` the
+/// most verbose
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace
+}
+
+/// The most verbose level let through, or `Off` for nothing at all
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LevelFilter {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace
+}
+
+/// Records more verbose than this are compiled out. It is `Trace` in debug
+/// builds and `Info` in release builds, unless one of the `log-max-level-*`
+/// features picks something else
+pub const STATIC_MAX_LEVEL: LevelFilter = if cfg!(feature = "log-max-level-off") {
+    LevelFilter::Off
+} else if cfg!(feature = "log-max-level-error") {
+    LevelFilter::Error
+} else if cfg!(feature = "log-max-level-warn") {
+    LevelFilter::Warn
+} else if cfg!(feature = "log-max-level-info") {
+    LevelFilter::Info
+} else if cfg!(feature = "log-max-level-debug") {
+    LevelFilter::Debug
+} else if cfg!(any(feature = "log-max-level-trace", debug_assertions)) {
+    LevelFilter::Trace
+} else {
+    LevelFilter::Info
+};
+
+const DEFAULT_LEVEL: LevelFilter = if cfg!(debug_assertions) {
+    LevelFilter::Debug
+} else {
+    LevelFilter::Info
+};
+
+/// A single message on its way to the sinks
+pub struct Record<'a> {
+    level: Level,
+    target: &'a str,
+    args: Arguments<'a>,
+    file: &'static str,
+    line: u32,
+    timestamp: Duration
+}
+
+/// Somewhere records end up. Sinks are called with the logger locked, so
+/// logging from inside one deadlocks
+pub trait Sink: Send {
+    fn write(&mut self, record: &Record<'_>);
+
+    fn flush(&mut self) {}
+}
+
+/// Writes each record as a line on stderr
+pub struct StderrSink;
+
+struct Logger {
+    sinks: Array<Box<dyn Sink>>,
+    default: LevelFilter,
+    targets: Array<(String, LevelFilter)>
+}
+
+static LOGGER: Mutex<Logger> = Mutex::new(Logger {
+    sinks: Array::new(),
+    default: DEFAULT_LEVEL,
+    targets: Array::new()
+});
+
+// The most verbose level any target lets through, so the macros can skip
+// disabled records without taking the lock
+static MAX_LEVEL: AtomicUsize = AtomicUsize::new(DEFAULT_LEVEL as usize);
+
+impl Level {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn  => "WARN",
+            Level::Info  => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE"
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.pad(self.as_str())
+    }
+}
+
+impl PartialEq<LevelFilter> for Level {
+    fn eq(&self, other: &LevelFilter) -> bool {
+        *self as usize == *other as usize
+    }
+}
+
+impl PartialOrd<LevelFilter> for Level {
+    fn partial_cmp(&self, other: &LevelFilter) -> Option<Ordering> {
+        Some((*self as usize).cmp(&(*other as usize)))
+    }
+}
+
+impl LevelFilter {
+    fn from_usize(level: usize) -> LevelFilter {
+        match level {
+            0 => LevelFilter::Off,
+            1 => LevelFilter::Error,
+            2 => LevelFilter::Warn,
+            3 => LevelFilter::Info,
+            4 => LevelFilter::Debug,
+            _ => LevelFilter::Trace
+        }
+    }
+}
+
+impl<'a> Record<'a> {
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// The module the record came from, unless the macro was given a
+    /// `target:`
+    pub fn target(&self) -> &'a str {
+        self.target
+    }
+
+    /// The formatted message
+    pub fn args(&self) -> &Arguments<'a> {
+        &self.args
+    }
+
+    pub fn file(&self) -> &'static str {
+        self.file
+    }
+
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Wall clock time the record was made, since the Unix epoch
+    pub fn timestamp(&self) -> Duration {
+        self.timestamp
+    }
+}
+
+/// `<timestamp> <LEVEL> <target>: <message>`, without a newline
+impl Display for Record<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write_line(f, self.timestamp, self.level, self.target, &self.args)
+    }
+}
+
+pub(crate) fn write_line(
+    f: &mut Formatter<'_>,
+    timestamp: Duration,
+    level: Level,
+    target: &str,
+    message: &dyn Display
+) -> FmtResult {
+    write!(f, "{} {:<5} {}: {}", Utc(timestamp), level, target, message)
+}
+
+/// Formats a time since the Unix epoch as `YYYY-MM-DD HH:MM:SS.mmm` in UTC
+pub(crate) struct Utc(pub(crate) Duration);
+
+impl Display for Utc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let secs = self.0.as_secs();
+        let (days, secs) = (secs / 86400, secs % 86400);
+
+        // Days to a civil date, from Howard Hinnant's date algorithms
+        let z = days as i64 + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}",
+            year, month, day,
+            secs / 3600, secs / 60 % 60, secs % 60,
+            self.0.subsec_millis()
+        )
+    }
+}
+
+impl Sink for StderrSink {
+    fn write(&mut self, record: &Record<'_>) {
+        let _ = writeln!(stderr().lock(), "{}", record);
+    }
+}
+
+impl Logger {
+    fn level_for(&self, target: &str) -> LevelFilter {
+        // The longest matching prefix wins, so `tortuga::render` can be
+        // quieter or louder than `tortuga`
+        let mut best: Option<&(String, LevelFilter)> = None;
+        for entry in self.targets.iter() {
+            let prefix = entry.0.as_str();
+            let matches = target.starts_with(prefix)
+                && (target.len() == prefix.len() || target[prefix.len()..].starts_with("::"));
+            if matches && best.is_none_or(|b| b.0.len() < prefix.len()) {
+                best = Some(entry);
+            }
+        }
+        best.map_or(self.default, |b| b.1)
+    }
+
+    fn update_max_level(&self) {
+        let max = self.targets.iter().map(|t| t.1).fold(self.default, |a, b| a.max(b));
+        MAX_LEVEL.store(max as usize, AtomicOrdering::Relaxed);
+    }
+}
+
+/// Sends records to `sink` as well as any sinks added before. Until the
+/// first one is added, records go to stderr
+#[cfg_attr(feature = "track-allocations", track_caller)]
+pub fn add_sink<S: Sink + 'static>(sink: S) {
+    let sink = into_dyn!(Box::new(sink), dyn Sink);
+    LOGGER.lock().sinks.push(sink);
+}
+
+/// Sets the level for targets without one of their own
+pub fn set_max_level(filter: LevelFilter) {
+    let mut logger = LOGGER.lock();
+    logger.default = filter;
+    logger.update_max_level();
+}
+
+/// Sets the level for `target` and the modules under it
+#[cfg_attr(feature = "track-allocations", track_caller)]
+pub fn set_target_level(target: &str, filter: LevelFilter) {
+    let mut logger = LOGGER.lock();
+    match logger.targets.iter_mut().find(|t| t.0 == target) {
+        Some(entry) => entry.1 = filter,
+        None => logger.targets.push((String::from(target), filter))
+    }
+    logger.update_max_level();
+}
+
+/// The most verbose level any target currently lets through
+pub fn max_level() -> LevelFilter {
+    LevelFilter::from_usize(MAX_LEVEL.load(AtomicOrdering::Relaxed))
+}
+
+pub fn enabled(level: Level, target: &str) -> bool {
+    level <= STATIC_MAX_LEVEL && level <= LOGGER.lock().level_for(target)
+}
+
+/// Flushes every sink
+pub fn flush() {
+    for sink in LOGGER.lock().sinks.iter_mut() {
+        sink.flush();
+    }
+}
+
+/// Flushes every sink unless the logger is in use, returning whether it
+/// did. The lock is held while records are formatted and written, so a
+/// panic handler must use this: the panic may have come from inside it
+pub fn try_flush() -> bool {
+    match LOGGER.try_lock() {
+        Some(mut logger) => {
+            for sink in logger.sinks.iter_mut() {
+                sink.flush();
+            }
+            true
+        }
+        None => false
+    }
+}
+
+#[doc(hidden)]
+pub fn __log(level: Level, target: &str, file: &'static str, line: u32, args: Arguments<'_>) {
+    let mut logger = LOGGER.lock();
+    if level > logger.level_for(target) {
+        return;
+    }
+
+    let record = Record {
+        level: level,
+        target: target,
+        args: args,
+        file: file,
+        line: line,
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+    };
+    if logger.sinks.is_empty() {
+        StderrSink.write(&record);
+    }
+    for sink in logger.sinks.iter_mut() {
+        sink.write(&record);
+    }
+}
+
+/// Logs a message at `level`. The target is the calling module unless one
+/// is given with `target:`
+///
+/// ```ignore
+/// log!(Level::Warn, "{} frames dropped", n);
+/// log!(target: "vulkan", Level::Error, "{}", message);
+/// ```
+#[macro_export]
+macro_rules! log {
+    (target: $target:expr, $level:expr, $($arg:tt)+) => {{
+        let level: $crate::log::Level = $level;
+        if level <= $crate::log::STATIC_MAX_LEVEL && level <= $crate::log::max_level() {
+            $crate::log::__log(level, $target, file!(), line!(), format_args!($($arg)+));
+        }
+    }};
+    ($level:expr, $($arg:tt)+) => {
+        $crate::log!(target: module_path!(), $level, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Error, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Error, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! warn {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Warn, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Warn, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! info {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Info, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Info, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! debug {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Debug, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Debug, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! trace {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Trace, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Trace, $($arg)+)
+    };
+}
@@ -0,0 +1,100 @@
+use crate::{
+    format,
+    fs::{remove_file, rename, Path, PathBuf},
+    io::{ErrorKind, File, OpenOptions, Result, Write}
+};
+use super::{Level, Record, Sink};
+
+/// Appends records to a file, rotating it once it grows past a size. The
+/// full log is renamed to `<path>.1`, older ones move up to
+/// `<path>.<max_files>`, and the one past that is deleted
+pub struct FileSink {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    max_len: u64,
+    max_files: usize
+}
+
+impl FileSink {
+    /// Opens `path` for appending, creating it if needed. With `max_files`
+    /// at zero a full log is cleared rather than kept
+    pub fn new<P: AsRef<Path>>(path: P, max_len: u64, max_files: usize) -> Result<FileSink> {
+        let path = path.as_ref().to_path_buf();
+        let file = FileSink::open(&path)?;
+        let len = file.metadata()?.len();
+
+        Ok(FileSink {
+            path: path,
+            file: file,
+            len: len,
+            max_len: max_len,
+            max_files: max_files
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn open(path: &Path) -> Result<File> {
+        OpenOptions::new().append(true).create(true).open(&path.to_c_string()?)
+    }
+
+    fn backup(&self, n: usize) -> PathBuf {
+        PathBuf::from(format!("{}.{}", self.path, n))
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        self.file.flush()?;
+
+        if self.max_files == 0 {
+            self.file = OpenOptions::new()
+                .write(true)
+                .truncate(true)
+                .create(true)
+                .open(&self.path.to_c_string()?)?;
+            self.len = 0;
+            return Ok(());
+        }
+
+        ignore_missing(remove_file(self.backup(self.max_files)))?;
+        for n in (1..self.max_files).rev() {
+            ignore_missing(rename(self.backup(n), self.backup(n + 1)))?;
+        }
+        rename(&self.path, self.backup(1))?;
+
+        self.file = FileSink::open(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+fn ignore_missing(result: Result<()>) -> Result<()> {
+    match result {
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        result => result
+    }
+}
+
+impl Sink for FileSink {
+    fn write(&mut self, record: &Record<'_>) {
+        let line = format!("{}\n", record);
+        if self.len > 0 && self.len + line.len() as u64 > self.max_len {
+            // Keep writing to the full file rather than lose the record
+            let _ = self.rotate();
+        }
+
+        if self.file.write_all(line.as_bytes()).is_ok() {
+            self.len += line.len() as u64;
+        }
+        // Problems should make it to disk even if the process dies next
+        if record.level() <= Level::Warn {
+            let _ = self.file.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        let _ = self.file.flush();
+    }
+}
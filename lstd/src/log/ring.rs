@@ -0,0 +1,109 @@
+use crate::{
+    container::Deque,
+    format,
+    string::String,
+    sync::{Arc, Mutex}
+};
+use super::{write_line, Level, Record, Sink};
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    time::Duration
+};
+
+/// Keeps the last `capacity` records in memory, for showing in an in-game
+/// console. Clones share the same buffer, so one can be added as a sink
+/// and another kept for reading
+#[derive(Clone)]
+pub struct RingBuffer {
+    entries: Arc<Mutex<Deque<Entry>>>,
+    capacity: usize
+}
+
+/// A record as kept by `RingBuffer`
+#[derive(Clone, Debug)]
+pub struct Entry {
+    level: Level,
+    target: String,
+    message: String,
+    timestamp: Duration
+}
+
+impl RingBuffer {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn new(capacity: usize) -> RingBuffer {
+        RingBuffer {
+            entries: Arc::new(Mutex::new(Deque::with_capacity(capacity))),
+            capacity: capacity
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+
+    /// Calls `f` on each entry, oldest first. The buffer stays locked
+    /// meanwhile, so `f` must not log
+    pub fn for_each<F: FnMut(&Entry)>(&self, mut f: F) {
+        for entry in self.entries.lock().iter() {
+            f(entry);
+        }
+    }
+}
+
+impl Sink for RingBuffer {
+    fn write(&mut self, record: &Record<'_>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let entry = Entry {
+            level: record.level(),
+            target: String::from(record.target()),
+            message: format!("{}", record.args()),
+            timestamp: record.timestamp()
+        };
+
+        let mut entries = self.entries.lock();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+}
+
+impl Entry {
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn timestamp(&self) -> Duration {
+        self.timestamp
+    }
+}
+
+/// Formatted the same way as `Record`
+impl Display for Entry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write_line(f, self.timestamp, self.level, &self.target, &self.message)
+    }
+}
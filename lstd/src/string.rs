@@ -0,0 +1,398 @@
+use crate::{
+    alloc::{Allocator, Malloc},
+    container::Array,
+    error::Error
+};
+use core::{
+    cmp::Ordering,
+    fmt::{self, Arguments, Debug, Display, Formatter, Result as FmtResult, Write},
+    hash::{Hash, Hasher},
+    iter::FromIterator,
+    ops::{Add, AddAssign, Deref, DerefMut},
+    str::{from_utf8, from_utf8_unchecked, from_utf8_unchecked_mut, Utf8Error}
+};
+
+/// A growable UTF-8 string stored in an `Array<u8>`. Everything `str` can
+/// do is available through `Deref`
+pub struct String<A: Allocator = Malloc> {
+    bytes: Array<u8, A>
+}
+
+/// Returned by `String::from_utf8` along with the bytes that were rejected
+pub struct FromUtf8Error<A: Allocator = Malloc> {
+    bytes: Array<u8, A>,
+    error: Utf8Error
+}
+
+impl String {
+    pub fn new() -> String {
+        String::new_in(Malloc)
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn with_capacity(cap: usize) -> String {
+        String::with_capacity_in(cap, Malloc)
+    }
+}
+
+impl<A: Allocator> String<A> {
+    pub fn new_in(alloc: A) -> String<A> {
+        String { bytes: Array::new_in(alloc) }
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn with_capacity_in(cap: usize, alloc: A) -> String<A> {
+        String { bytes: Array::with_capacity_in(cap, alloc) }
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn from_str_in(s: &str, alloc: A) -> String<A> {
+        String { bytes: Array::from_slice_in(s.as_bytes(), alloc) }
+    }
+
+    pub fn from_utf8(bytes: Array<u8, A>) -> Result<String<A>, FromUtf8Error<A>> {
+        match from_utf8(&bytes) {
+            Ok(_) => Ok(String { bytes: bytes }),
+            Err(error) => Err(FromUtf8Error { bytes: bytes, error: error })
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The bytes must be valid UTF-8
+    pub unsafe fn from_utf8_unchecked(bytes: Array<u8, A>) -> String<A> {
+        String { bytes: bytes }
+    }
+
+    pub fn into_bytes(self) -> Array<u8, A> {
+        self.bytes
+    }
+
+    pub fn as_str(&self) -> &str {
+        unsafe { from_utf8_unchecked(&self.bytes) }
+    }
+
+    pub fn as_mut_str(&mut self) -> &mut str {
+        unsafe { from_utf8_unchecked_mut(&mut self.bytes) }
+    }
+
+    /// Gives access to the raw bytes
+    ///
+    /// # Safety
+    ///
+    /// They must still be valid UTF-8 when the borrow ends
+    pub unsafe fn as_mut_array(&mut self) -> &mut Array<u8, A> {
+        &mut self.bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.bytes.capacity()
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn reserve(&mut self, additional: usize) {
+        self.bytes.reserve(additional);
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn push(&mut self, c: char) {
+        let mut buffer = [0; 4];
+        self.push_str(c.encode_utf8(&mut buffer));
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn push_str(&mut self, s: &str) {
+        self.bytes.extend_from_slice(s.as_bytes());
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.chars().next_back()?;
+        self.bytes.truncate(self.len() - c.len_utf8());
+        Some(c)
+    }
+
+    /// Inserts a character at byte offset `index`, which must be on a
+    /// character boundary
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn insert(&mut self, index: usize, c: char) {
+        let mut buffer = [0; 4];
+        self.insert_str(index, c.encode_utf8(&mut buffer));
+    }
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn insert_str(&mut self, index: usize, s: &str) {
+        assert!(self.is_char_boundary(index));
+
+        self.bytes.extend_from_slice(s.as_bytes());
+        self.bytes[index..].rotate_right(s.len());
+    }
+
+    /// Removes the character at byte offset `index`
+    pub fn remove(&mut self, index: usize) -> char {
+        let c = self[index..].chars().next().unwrap();
+        self.bytes.drain(index..index + c.len_utf8());
+        c
+    }
+
+    /// Shortens the string to `len` bytes, which must be on a character
+    /// boundary
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            assert!(self.is_char_boundary(len));
+            self.bytes.truncate(len);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+
+    pub fn retain<F: FnMut(char) -> bool>(&mut self, mut f: F) {
+        let mut i = 0;
+        while i < self.len() {
+            let c = self[i..].chars().next().unwrap();
+            if f(c) {
+                i += c.len_utf8();
+            } else {
+                self.bytes.drain(i..i + c.len_utf8());
+            }
+        }
+    }
+}
+
+impl<A: Allocator + Clone> String<A> {
+    /// Returns a copy with every occurrence of `from` replaced by `to`
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn replace(&self, from: &str, to: &str) -> String<A> {
+        let mut result = String::with_capacity_in(self.len(), self.bytes.allocator().clone());
+        let mut last = 0;
+        for (start, part) in self.match_indices(from) {
+            result.push_str(&self[last..start]);
+            result.push_str(to);
+            last = start + part.len();
+        }
+        result.push_str(&self[last..]);
+        result
+    }
+
+    /// Returns a copy with ASCII letters lowercased. Other characters are
+    /// left as they are
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn to_lowercase(&self) -> String<A> {
+        let mut result = self.clone();
+        result.make_ascii_lowercase();
+        result
+    }
+
+    /// Returns a copy with ASCII letters uppercased
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn to_uppercase(&self) -> String<A> {
+        let mut result = self.clone();
+        result.make_ascii_uppercase();
+        result
+    }
+}
+
+/// The function behind `format!`
+#[cfg_attr(feature = "track-allocations", track_caller)]
+pub fn format(args: Arguments<'_>) -> String {
+    let mut s = String::new();
+    let _ = s.write_fmt(args);
+    s
+}
+
+impl<A: Allocator> FromUtf8Error<A> {
+    pub fn into_bytes(self) -> Array<u8, A> {
+        self.bytes
+    }
+
+    pub fn utf8_error(&self) -> Utf8Error {
+        self.error
+    }
+}
+
+impl<A: Allocator> Debug for FromUtf8Error<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(&self.error, f)
+    }
+}
+
+impl<A: Allocator> Display for FromUtf8Error<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(&self.error, f)
+    }
+}
+
+impl<A: Allocator> Error for FromUtf8Error<A> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<A: Allocator> Deref for String<A> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<A: Allocator> DerefMut for String<A> {
+    fn deref_mut(&mut self) -> &mut str {
+        self.as_mut_str()
+    }
+}
+
+impl<A: Allocator + Clone> Clone for String<A> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn clone(&self) -> String<A> {
+        String { bytes: self.bytes.clone() }
+    }
+}
+
+impl Default for String {
+    fn default() -> String {
+        String::new()
+    }
+}
+
+impl From<&str> for String {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn from(s: &str) -> String {
+        String::from_str_in(s, Malloc)
+    }
+}
+
+impl From<char> for String {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn from(c: char) -> String {
+        let mut s = String::new();
+        s.push(c);
+        s
+    }
+}
+
+impl<A: Allocator> fmt::Write for String<A> {
+    fn write_str(&mut self, s: &str) -> FmtResult {
+        self.push_str(s);
+        Ok(())
+    }
+
+    fn write_char(&mut self, c: char) -> FmtResult {
+        self.push(c);
+        Ok(())
+    }
+}
+
+impl<A: Allocator> Debug for String<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<A: Allocator> Display for String<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<A: Allocator, B: Allocator> PartialEq<String<B>> for String<A> {
+    fn eq(&self, other: &String<B>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<A: Allocator> Eq for String<A> {}
+
+impl<A: Allocator> PartialEq<str> for String<A> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a, A: Allocator> PartialEq<&'a str> for String<A> {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<A: Allocator> PartialOrd for String<A> {
+    fn partial_cmp(&self, other: &String<A>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<A: Allocator> Ord for String<A> {
+    fn cmp(&self, other: &String<A>) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<A: Allocator> Hash for String<A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl<A: Allocator> Add<&str> for String<A> {
+    type Output = String<A>;
+
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn add(mut self, other: &str) -> String<A> {
+        self.push_str(other);
+        self
+    }
+}
+
+impl<A: Allocator> AddAssign<&str> for String<A> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn add_assign(&mut self, other: &str) {
+        self.push_str(other);
+    }
+}
+
+impl<A: Allocator> Extend<char> for String<A> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for c in iter {
+            self.push(c);
+        }
+    }
+}
+
+impl<'a, A: Allocator> Extend<&'a str> for String<A> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for s in iter {
+            self.push_str(s);
+        }
+    }
+}
+
+impl FromIterator<char> for String {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> String {
+        let mut s = String::new();
+        s.extend(iter);
+        s
+    }
+}
+
+impl<'a> FromIterator<&'a str> for String {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> String {
+        let mut s = String::new();
+        s.extend(iter);
+        s
+    }
+}
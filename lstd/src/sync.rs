@@ -0,0 +1,18 @@
+pub mod mpsc;
+pub mod spsc;
+
+mod arc;
+mod atomic_cell;
+mod barrier;
+mod condvar;
+mod mutex;
+mod once;
+mod rwlock;
+
+pub use arc::*;
+pub use atomic_cell::*;
+pub use barrier::*;
+pub use condvar::*;
+pub use mutex::*;
+pub use once::*;
+pub use rwlock::*;
@@ -0,0 +1,346 @@
+use crate::{
+    abort,
+    alloc::{handle_alloc_error, Allocator, Malloc}
+};
+use core::{
+    alloc::Layout,
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    hint::spin_loop,
+    mem::{forget, ManuallyDrop},
+    ops::{Deref, Drop},
+    ptr::{drop_in_place, read, NonNull},
+    sync::atomic::{fence, AtomicUsize, Ordering}
+};
+
+/// Counts past this are assumed to come from leaked handles and abort
+/// before they can wrap around
+const MAX_REFCOUNT: usize = isize::MAX as usize;
+
+/// Stored in the weak count while `get_mut` checks for other handles
+const WEAK_LOCKED: usize = usize::MAX;
+
+/// The single allocation behind an `Arc` and its `Weak` handles. As with
+/// `Rc`, all strong references together hold one weak reference
+struct ArcInner<T> {
+    strong: AtomicUsize,
+    weak: AtomicUsize,
+    value: T
+}
+
+/// A reference-counted pointer whose counts are atomic, so it can be shared
+/// between threads. Use `Weak` for back-references
+pub struct Arc<T, A: Allocator = Malloc> {
+    ptr: NonNull<ArcInner<T>>,
+    // Taken out in `drop`, which may need to move it into a `Weak`
+    alloc: ManuallyDrop<A>
+}
+
+/// A non-owning reference to a value managed by `Arc`
+pub struct Weak<T, A: Allocator = Malloc> {
+    ptr: NonNull<ArcInner<T>>,
+    alloc: A
+}
+
+unsafe impl<T: Send + Sync, A: Allocator + Send> Send for Arc<T, A> {}
+unsafe impl<T: Send + Sync, A: Allocator + Sync> Sync for Arc<T, A> {}
+unsafe impl<T: Send + Sync, A: Allocator + Send> Send for Weak<T, A> {}
+unsafe impl<T: Send + Sync, A: Allocator + Sync> Sync for Weak<T, A> {}
+
+fn increment(count: &AtomicUsize) {
+    if count.fetch_add(1, Ordering::Relaxed) > MAX_REFCOUNT {
+        abort();
+    }
+}
+
+impl<T> Arc<T> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn new(value: T) -> Arc<T> {
+        Arc::new_in(value, Malloc)
+    }
+}
+
+impl<T, A: Allocator> Arc<T, A> {
+    #[cfg_attr(feature = "track-allocations", track_caller)]
+    pub fn new_in(value: T, alloc: A) -> Arc<T, A> {
+        let layout = Layout::new::<ArcInner<T>>();
+        let ptr: NonNull<ArcInner<T>> = match alloc.allocate(layout) {
+            Some(ptr) => ptr.cast(),
+            None => handle_alloc_error(layout)
+        };
+
+        unsafe {
+            ptr.as_ptr().write(ArcInner {
+                strong: AtomicUsize::new(1),
+                weak: AtomicUsize::new(1),
+                value: value
+            });
+        }
+
+        Arc { ptr: ptr, alloc: ManuallyDrop::new(alloc) }
+    }
+
+    /// Returns a mutable reference to the value if no other `Arc` or `Weak`
+    /// points to it. Holding `&mut` to the only handle means no new ones can
+    /// appear while the reference is alive
+    pub fn get_mut(this: &mut Arc<T, A>) -> Option<&mut T> {
+        if Arc::is_unique(this) {
+            unsafe { Some(&mut (*this.ptr.as_ptr()).value) }
+        } else {
+            None
+        }
+    }
+
+    /// Reading the two counts separately would let another thread upgrade a
+    /// `Weak` and drop it in between, so the weak count is locked while the
+    /// strong count is checked. `downgrade` waits for the lock, and with no
+    /// `Weak` around there is nothing to upgrade
+    fn is_unique(this: &mut Arc<T, A>) -> bool {
+        let weak = &this.inner().weak;
+        if weak.compare_exchange(1, WEAK_LOCKED, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return false;
+        }
+
+        let unique = this.inner().strong.load(Ordering::Acquire) == 1;
+        weak.store(1, Ordering::Release);
+        unique
+    }
+
+    /// Moves the value out if this is the only strong reference
+    pub fn try_unwrap(this: Arc<T, A>) -> Result<T, Arc<T, A>> {
+        let unique = this.inner().strong
+            .compare_exchange(1, 0, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok();
+        if !unique {
+            return Err(this);
+        }
+        fence(Ordering::Acquire);
+
+        unsafe {
+            let value = read(&this.inner().value);
+
+            let ptr = this.ptr;
+            let alloc = ManuallyDrop::into_inner(read(&this.alloc));
+            forget(this);
+            drop(Weak { ptr: ptr, alloc: alloc });
+
+            Ok(value)
+        }
+    }
+
+    pub fn ptr_eq(this: &Arc<T, A>, other: &Arc<T, A>) -> bool {
+        this.ptr == other.ptr
+    }
+
+    /// The count can change as soon as it is read if other threads hold
+    /// handles too
+    pub fn strong_count(this: &Arc<T, A>) -> usize {
+        this.inner().strong.load(Ordering::Acquire)
+    }
+
+    pub fn weak_count(this: &Arc<T, A>) -> usize {
+        match this.inner().weak.load(Ordering::Acquire) {
+            // Only locked while `get_mut` finds there are no `Weak`s
+            WEAK_LOCKED => 0,
+            n => n - 1
+        }
+    }
+
+    pub fn as_ptr(this: &Arc<T, A>) -> *mut T {
+        unsafe { &mut (*this.ptr.as_ptr()).value as *mut T }
+    }
+
+    fn inner(&self) -> &ArcInner<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T, A: Allocator + Clone> Arc<T, A> {
+    pub fn downgrade(this: &Arc<T, A>) -> Weak<T, A> {
+        let weak = &this.inner().weak;
+        let mut n = weak.load(Ordering::Relaxed);
+        loop {
+            // Another thread's `get_mut` is checking the counts
+            if n == WEAK_LOCKED {
+                spin_loop();
+                n = weak.load(Ordering::Relaxed);
+                continue;
+            }
+            if n > MAX_REFCOUNT { abort(); }
+
+            match weak.compare_exchange_weak(
+                n,
+                n + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed
+            ) {
+                Ok(_) => return Weak { ptr: this.ptr, alloc: A::clone(&this.alloc) },
+                Err(old) => n = old
+            }
+        }
+    }
+}
+
+impl<T, A: Allocator + Clone> Clone for Arc<T, A> {
+    fn clone(&self) -> Arc<T, A> {
+        increment(&self.inner().strong);
+        Arc { ptr: self.ptr, alloc: self.alloc.clone() }
+    }
+}
+
+impl<T, A: Allocator> Deref for Arc<T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner().value
+    }
+}
+
+impl<T, A: Allocator> Drop for Arc<T, A> {
+    fn drop(&mut self) {
+        // Taken out exactly once, and dropped here unless a `Weak` takes it
+        let alloc = unsafe { ManuallyDrop::take(&mut self.alloc) };
+        if self.inner().strong.fetch_sub(1, Ordering::Release) != 1 { return; }
+
+        // Make every other handle's use of the value happen before it is
+        // dropped here
+        fence(Ordering::Acquire);
+
+        unsafe {
+            drop_in_place(&mut (*self.ptr.as_ptr()).value);
+            drop(Weak { ptr: self.ptr, alloc: alloc });
+        }
+    }
+}
+
+impl<T: Debug, A: Allocator> Debug for Arc<T, A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Display, A: Allocator> Display for Arc<T, A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq, A: Allocator> PartialEq for Arc<T, A> {
+    fn eq(&self, other: &Arc<T, A>) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq, A: Allocator> Eq for Arc<T, A> {}
+
+impl<T, A: Allocator> Weak<T, A> {
+    pub fn strong_count(&self) -> usize {
+        self.inner().strong.load(Ordering::Acquire)
+    }
+
+    pub fn ptr_eq(&self, other: &Weak<T, A>) -> bool {
+        self.ptr == other.ptr
+    }
+
+    fn inner(&self) -> &ArcInner<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T, A: Allocator + Clone> Weak<T, A> {
+    /// Returns a new strong reference, or `None` if the value has already
+    /// been dropped. The count is never raised from zero, so a value that
+    /// is being dropped can't be revived. This `Weak` keeps the weak count
+    /// above one, so `get_mut` can't be running against the same value
+    pub fn upgrade(&self) -> Option<Arc<T, A>> {
+        let strong = &self.inner().strong;
+        let mut n = strong.load(Ordering::Relaxed);
+        loop {
+            if n == 0 { return None; }
+            if n > MAX_REFCOUNT { abort(); }
+
+            match strong.compare_exchange_weak(
+                n,
+                n + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed
+            ) {
+                Ok(_) => return Some(Arc { ptr: self.ptr, alloc: ManuallyDrop::new(self.alloc.clone()) }),
+                Err(old) => n = old
+            }
+        }
+    }
+}
+
+impl<T, A: Allocator + Clone> Clone for Weak<T, A> {
+    fn clone(&self) -> Weak<T, A> {
+        increment(&self.inner().weak);
+        Weak { ptr: self.ptr, alloc: self.alloc.clone() }
+    }
+}
+
+impl<T, A: Allocator> Drop for Weak<T, A> {
+    fn drop(&mut self) {
+        if self.inner().weak.fetch_sub(1, Ordering::Release) != 1 { return; }
+
+        fence(Ordering::Acquire);
+        unsafe {
+            self.alloc.deallocate(self.ptr.cast(), Layout::new::<ArcInner<T>>());
+        }
+    }
+}
+
+impl<T, A: Allocator> Debug for Weak<T, A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("(Weak)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        alloc::testing::{Counting, Counts},
+        thread
+    };
+
+    #[test]
+    fn get_mut_only_when_unique() {
+        let mut arc = Arc::new(1u32);
+        let weak = Arc::downgrade(&arc);
+        assert!(Arc::get_mut(&mut arc).is_none());
+        drop(weak);
+
+        *Arc::get_mut(&mut arc).unwrap() = 2;
+        assert_eq!(Arc::weak_count(&arc), 0);
+        drop(Arc::downgrade(&arc));
+        assert_eq!(*arc, 2);
+    }
+
+    #[test]
+    fn last_drop_on_another_thread() {
+        static COUNTS: Counts = Counts::new();
+
+        let arc = Arc::new_in(5u64, Counting(&COUNTS));
+        let other = arc.clone();
+        drop(arc);
+        assert_eq!(COUNTS.deallocations(), 0);
+
+        thread::spawn(move || assert_eq!(*other, 5)).join();
+        assert_eq!(COUNTS.allocations(), 1);
+        assert_eq!(COUNTS.deallocations(), 1);
+        assert_eq!(COUNTS.drops(), 2);
+    }
+
+    #[test]
+    fn try_unwrap_needs_the_last_strong_reference() {
+        let counts = Counts::new();
+        let arc = Arc::new_in(6u32, Counting(&counts));
+        let other = arc.clone();
+        let arc = Arc::try_unwrap(arc).unwrap_err();
+        drop(other);
+
+        assert_eq!(Arc::try_unwrap(arc).ok(), Some(6));
+        assert_eq!(counts.deallocations(), 1);
+        assert_eq!(counts.drops(), 2);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::alloc::spin::SpinLock;
+use core::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    mem::replace
+};
+
+/// A thread-safe cell for `Copy` values of any size. Every access takes a
+/// short spin lock, which keeps it simple and correct for values that don't
+/// fit in a native atomic; use `core::sync::atomic` for plain integers
+pub struct AtomicCell<T> {
+    value: SpinLock<T>
+}
+
+unsafe impl<T: Send> Send for AtomicCell<T> {}
+unsafe impl<T: Send> Sync for AtomicCell<T> {}
+
+impl<T> AtomicCell<T> {
+    pub const fn new(value: T) -> AtomicCell<T> {
+        AtomicCell { value: SpinLock::new(value) }
+    }
+
+    pub fn store(&self, value: T) {
+        drop(self.swap(value));
+    }
+
+    pub fn swap(&self, value: T) -> T {
+        self.value.with(|v| replace(v, value))
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+impl<T: Copy> AtomicCell<T> {
+    pub fn load(&self) -> T {
+        self.value.with(|v| *v)
+    }
+
+    /// Applies `f` to the current value and stores the result, returning
+    /// the previous value. `f` runs with the lock held, so it must be short
+    pub fn fetch_update<F: FnOnce(T) -> T>(&self, f: F) -> T {
+        self.value.with(|v| {
+            let old = *v;
+            *v = f(old);
+            old
+        })
+    }
+}
+
+impl<T: Copy + PartialEq> AtomicCell<T> {
+    /// Stores `new` if the current value equals `current`. Returns the
+    /// previous value either way, as `Ok` if it was replaced
+    pub fn compare_exchange(&self, current: T, new: T) -> Result<T, T> {
+        self.value.with(|v| {
+            let old = *v;
+            if old == current {
+                *v = new;
+                Ok(old)
+            } else {
+                Err(old)
+            }
+        })
+    }
+}
+
+impl<T: Default> AtomicCell<T> {
+    pub fn take(&self) -> T {
+        self.swap(T::default())
+    }
+}
+
+impl<T: Default> Default for AtomicCell<T> {
+    fn default() -> AtomicCell<T> {
+        AtomicCell::new(T::default())
+    }
+}
+
+impl<T: Copy + Debug> Debug for AtomicCell<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(&self.load(), f)
+    }
+}
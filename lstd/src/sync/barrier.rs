@@ -0,0 +1,42 @@
+use crate::sync::{Condvar, Mutex};
+
+/// Lets a fixed number of threads wait for each other. The barrier can be
+/// reused once everyone has passed it
+pub struct Barrier {
+    state: Mutex<BarrierState>,
+    condvar: Condvar,
+    threads: usize
+}
+
+struct BarrierState {
+    waiting: usize,
+    generation: usize
+}
+
+impl Barrier {
+    pub const fn new(threads: usize) -> Barrier {
+        Barrier {
+            state: Mutex::new(BarrierState { waiting: 0, generation: 0 }),
+            condvar: Condvar::new(),
+            threads: threads
+        }
+    }
+
+    /// Blocks until `threads` threads have called `wait`. Returns true on
+    /// exactly one of them, which can then do any work that follows
+    pub fn wait(&self) -> bool {
+        let mut state = self.state.lock();
+        let generation = state.generation;
+        state.waiting += 1;
+
+        if state.waiting < self.threads {
+            let _state = self.condvar.wait_while(state, |s| s.generation == generation);
+            false
+        } else {
+            state.waiting = 0;
+            state.generation = state.generation.wrapping_add(1);
+            self.condvar.notify_all();
+            true
+        }
+    }
+}
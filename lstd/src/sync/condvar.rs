@@ -0,0 +1,97 @@
+use crate::sync::MutexGuard;
+use c::{
+    errno::ETIMEDOUT,
+    pthread::*,
+    time::{clock_gettime, timespec, CLOCK_REALTIME}
+};
+use core::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    ops::Drop,
+    time::Duration
+};
+
+/// A condition variable for waiting on a `Mutex`. Waits can wake up
+/// spuriously, so the condition must be checked again after each one;
+/// `wait_while` does that
+pub struct Condvar {
+    raw: UnsafeCell<pthread_cond_t>
+}
+
+unsafe impl Send for Condvar {}
+unsafe impl Sync for Condvar {}
+
+impl Condvar {
+    pub const fn new() -> Condvar {
+        Condvar { raw: UnsafeCell::new(PTHREAD_COND_INITIALIZER) }
+    }
+
+    pub fn wait<'a, T>(&self, guard: MutexGuard<'a, T>) -> MutexGuard<'a, T> {
+        unsafe { pthread_cond_wait(self.raw.get(), guard.mutex.raw()); }
+        guard
+    }
+
+    pub fn wait_while<'a, T, F: FnMut(&mut T) -> bool>(
+        &self,
+        mut guard: MutexGuard<'a, T>,
+        mut condition: F
+    ) -> MutexGuard<'a, T> {
+        while condition(&mut *guard) {
+            guard = self.wait(guard);
+        }
+        guard
+    }
+
+    /// Waits for a notification or until `timeout` has passed. The flag is
+    /// true if the wait timed out
+    pub fn wait_timeout<'a, T>(
+        &self,
+        guard: MutexGuard<'a, T>,
+        timeout: Duration
+    ) -> (MutexGuard<'a, T>, bool) {
+        let deadline = deadline_after(timeout);
+        let rc = unsafe {
+            pthread_cond_timedwait(self.raw.get(), guard.mutex.raw(), &deadline)
+        };
+        (guard, rc == ETIMEDOUT)
+    }
+
+    pub fn notify_one(&self) {
+        unsafe { pthread_cond_signal(self.raw.get()); }
+    }
+
+    pub fn notify_all(&self) {
+        unsafe { pthread_cond_broadcast(self.raw.get()); }
+    }
+}
+
+/// Timed waits take an absolute time on the realtime clock, which is what a
+/// statically initialised condition variable uses
+fn deadline_after(timeout: Duration) -> timespec {
+    let now = unsafe {
+        let mut now = MaybeUninit::<timespec>::uninit();
+        clock_gettime(CLOCK_REALTIME, now.as_mut_ptr());
+        now.assume_init()
+    };
+
+    let nanos = now.tv_nsec as u64 + timeout.subsec_nanos() as u64;
+    let secs = (now.tv_sec as u64)
+        .saturating_add(timeout.as_secs())
+        .saturating_add(nanos / 1_000_000_000);
+    timespec {
+        tv_sec: if secs > i64::MAX as u64 { i64::MAX } else { secs as i64 },
+        tv_nsec: (nanos % 1_000_000_000) as i64
+    }
+}
+
+impl Drop for Condvar {
+    fn drop(&mut self) {
+        unsafe { pthread_cond_destroy(self.raw.get_mut()); }
+    }
+}
+
+impl Default for Condvar {
+    fn default() -> Condvar {
+        Condvar::new()
+    }
+}
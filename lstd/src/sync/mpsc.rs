@@ -0,0 +1,270 @@
+use crate::{
+    container::Deque,
+    sync::{Arc, Condvar, Mutex},
+    time::Instant
+};
+use core::{
+    cell::Cell,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    marker::PhantomData,
+    ops::Drop,
+    time::Duration
+};
+
+struct State<T> {
+    queue: Deque<T>,
+    senders: usize,
+    receiver: bool
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    bound: Option<usize>
+}
+
+/// The sending half of a channel. It can be cloned to send from several
+/// threads
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>
+}
+
+/// The receiving half of a channel. There is only ever one
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    _not_sync: PhantomData<Cell<()>>
+}
+
+/// The receiver is gone. The value that could not be sent is handed back
+pub struct SendError<T>(pub T);
+
+pub enum TrySendError<T> {
+    Full(T),
+    Disconnected(T)
+}
+
+/// Every sender is gone and the channel is empty
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecvError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    Timeout,
+    Disconnected
+}
+
+fn new_channel<T>(bound: Option<usize>) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            queue: Deque::new(),
+            senders: 1,
+            receiver: true
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+        bound: bound
+    });
+
+    let receiver = Receiver { shared: shared.clone(), _not_sync: PhantomData };
+    (Sender { shared: shared }, receiver)
+}
+
+/// Creates a channel that buffers any number of messages, so sending never
+/// blocks
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    new_channel(None)
+}
+
+/// Creates a channel that holds at most `bound` messages, after which
+/// `send` blocks until the receiver catches up. A bound of zero is treated
+/// as one
+pub fn bounded<T>(bound: usize) -> (Sender<T>, Receiver<T>) {
+    new_channel(Some(if bound == 0 { 1 } else { bound }))
+}
+
+impl<T> Sender<T> {
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let shared = &*self.shared;
+        let mut state = shared.state.lock();
+        if let Some(bound) = shared.bound {
+            state = shared.not_full.wait_while(state, |s| {
+                s.receiver && s.queue.len() >= bound
+            });
+        }
+
+        if !state.receiver {
+            return Err(SendError(value));
+        }
+
+        state.queue.push_back(value);
+        drop(state);
+        shared.not_empty.notify_one();
+        Ok(())
+    }
+
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        let shared = &*self.shared;
+        let mut state = shared.state.lock();
+        if !state.receiver {
+            return Err(TrySendError::Disconnected(value));
+        }
+        if let Some(bound) = shared.bound {
+            if state.queue.len() >= bound {
+                return Err(TrySendError::Full(value));
+            }
+        }
+
+        state.queue.push_back(value);
+        drop(state);
+        shared.not_empty.notify_one();
+        Ok(())
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.shared.state.lock().senders += 1;
+        Sender { shared: self.shared.clone() }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            drop(state);
+            self.shared.not_empty.notify_all();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Blocks until a message arrives. Messages sent before the last sender
+    /// went away are still delivered
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let shared = &*self.shared;
+        let state = shared.state.lock();
+        let mut state = shared.not_empty.wait_while(state, |s| {
+            s.queue.is_empty() && s.senders > 0
+        });
+
+        match state.queue.pop_front() {
+            Some(value) => {
+                drop(state);
+                shared.not_full.notify_one();
+                Ok(value)
+            }
+            None => Err(RecvError)
+        }
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let shared = &*self.shared;
+        let mut state = shared.state.lock();
+        match state.queue.pop_front() {
+            Some(value) => {
+                drop(state);
+                shared.not_full.notify_one();
+                Ok(value)
+            }
+            None if state.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty)
+        }
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let shared = &*self.shared;
+        let deadline = Instant::now() + timeout;
+        let mut state = shared.state.lock();
+        loop {
+            if let Some(value) = state.queue.pop_front() {
+                drop(state);
+                shared.not_full.notify_one();
+                return Ok(value);
+            }
+            if state.senders == 0 {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(RecvTimeoutError::Timeout);
+            }
+            state = shared.not_empty.wait_timeout(state, deadline - now).0;
+        }
+    }
+
+    /// Iterates over messages, blocking for each, until every sender is
+    /// gone
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { receiver: self }
+    }
+
+    /// Iterates over the messages that are already waiting
+    pub fn try_iter(&self) -> TryIter<'_, T> {
+        TryIter { receiver: self }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.state.lock().receiver = false;
+        self.shared.not_full.notify_all();
+    }
+}
+
+pub struct Iter<'a, T> {
+    receiver: &'a Receiver<T>
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.receiver.recv().ok()
+    }
+}
+
+pub struct TryIter<'a, T> {
+    receiver: &'a Receiver<T>
+}
+
+impl<'a, T> Iterator for TryIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Receiver<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> Debug for SendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("SendError(..)")
+    }
+}
+
+impl<T> Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            TrySendError::Full(_) => f.write_str("Full(..)"),
+            TrySendError::Disconnected(_) => f.write_str("Disconnected(..)")
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use c::pthread::*;
+use core::{
+    cell::UnsafeCell,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    marker::PhantomData,
+    ops::{Deref, DerefMut, Drop}
+};
+
+/// A mutual exclusion lock around a pthread mutex. There is no poisoning
+/// since panics abort. The pthread mutex is stored inline so that `new` can
+/// be used in statics; glibc's default mutex has no state that ties it to
+/// its address while it is unlocked, and a locked one can't be moved
+pub struct Mutex<T> {
+    raw: UnsafeCell<pthread_mutex_t>,
+    value: UnsafeCell<T>
+}
+
+unsafe impl<T: Send> Send for Mutex<T> {}
+unsafe impl<T: Send> Sync for Mutex<T> {}
+
+/// Unlocks the mutex when dropped. It must stay on the thread that locked
+pub struct MutexGuard<'a, T> {
+    pub(super) mutex: &'a Mutex<T>,
+    _not_send: PhantomData<*const ()>
+}
+
+unsafe impl<'a, T: Sync> Sync for MutexGuard<'a, T> {}
+
+impl<T> Mutex<T> {
+    pub const fn new(value: T) -> Mutex<T> {
+        Mutex {
+            raw: UnsafeCell::new(PTHREAD_MUTEX_INITIALIZER),
+            value: UnsafeCell::new(value)
+        }
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        unsafe { pthread_mutex_lock(self.raw()); }
+        MutexGuard { mutex: self, _not_send: PhantomData }
+    }
+
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        if unsafe { pthread_mutex_trylock(self.raw()) } == 0 {
+            Some(MutexGuard { mutex: self, _not_send: PhantomData })
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        let value = unsafe { core::ptr::read(self.value.get()) };
+        let mut this = core::mem::ManuallyDrop::new(self);
+        unsafe { pthread_mutex_destroy(this.raw.get_mut()); }
+        value
+    }
+
+    pub(super) fn raw(&self) -> *mut pthread_mutex_t {
+        self.raw.get()
+    }
+}
+
+impl<T> Drop for Mutex<T> {
+    fn drop(&mut self) {
+        unsafe { pthread_mutex_destroy(self.raw.get_mut()); }
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Mutex<T> {
+        Mutex::new(T::default())
+    }
+}
+
+impl<T: Debug> Debug for Mutex<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.try_lock() {
+            Some(guard) => f.debug_struct("Mutex").field("value", &*guard).finish(),
+            None => f.write_str("Mutex { <locked> }")
+        }
+    }
+}
+
+impl<'a, T> Deref for MutexGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.value.get() }
+    }
+}
+
+impl<'a, T> DerefMut for MutexGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.value.get() }
+    }
+}
+
+impl<'a, T> Drop for MutexGuard<'a, T> {
+    fn drop(&mut self) {
+        unsafe { pthread_mutex_unlock(self.mutex.raw()); }
+    }
+}
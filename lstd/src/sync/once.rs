@@ -0,0 +1,38 @@
+use crate::sync::Mutex;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Runs a piece of initialisation exactly once, however many threads ask
+/// for it. Callers that arrive while it is running wait for it to finish
+pub struct Once {
+    done: AtomicBool,
+    lock: Mutex<()>
+}
+
+impl Once {
+    pub const fn new() -> Once {
+        Once {
+            done: AtomicBool::new(false),
+            lock: Mutex::new(())
+        }
+    }
+
+    pub fn call_once<F: FnOnce()>(&self, f: F) {
+        if self.is_completed() { return; }
+
+        let _guard = self.lock.lock();
+        if !self.done.load(Ordering::Relaxed) {
+            f();
+            self.done.store(true, Ordering::Release);
+        }
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.done.load(Ordering::Acquire)
+    }
+}
+
+impl Default for Once {
+    fn default() -> Once {
+        Once::new()
+    }
+}
@@ -0,0 +1,150 @@
+use c::{
+    errno::EDEADLK,
+    pthread::*,
+    types::c_int
+};
+use core::{
+    cell::UnsafeCell,
+    marker::PhantomData,
+    ops::{Deref, DerefMut, Drop}
+};
+
+/// A reader-writer lock around a pthread rwlock. Any number of readers or
+/// a single writer can hold it at a time
+pub struct RwLock<T> {
+    raw: UnsafeCell<pthread_rwlock_t>,
+    value: UnsafeCell<T>
+}
+
+unsafe impl<T: Send> Send for RwLock<T> {}
+unsafe impl<T: Send + Sync> Sync for RwLock<T> {}
+
+pub struct RwLockReadGuard<'a, T> {
+    lock: &'a RwLock<T>,
+    _not_send: PhantomData<*const ()>
+}
+
+pub struct RwLockWriteGuard<'a, T> {
+    lock: &'a RwLock<T>,
+    _not_send: PhantomData<*const ()>
+}
+
+unsafe impl<'a, T: Sync> Sync for RwLockReadGuard<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for RwLockWriteGuard<'a, T> {}
+
+impl<T> RwLock<T> {
+    pub const fn new(value: T) -> RwLock<T> {
+        RwLock {
+            raw: UnsafeCell::new(PTHREAD_RWLOCK_INITIALIZER),
+            value: UnsafeCell::new(value)
+        }
+    }
+
+    /// Panics if the calling thread already holds the write lock
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        check(unsafe { pthread_rwlock_rdlock(self.raw.get()) });
+        RwLockReadGuard { lock: self, _not_send: PhantomData }
+    }
+
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        if unsafe { pthread_rwlock_tryrdlock(self.raw.get()) } == 0 {
+            Some(RwLockReadGuard { lock: self, _not_send: PhantomData })
+        } else {
+            None
+        }
+    }
+
+    /// Panics if the calling thread already holds the write lock
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        check(unsafe { pthread_rwlock_wrlock(self.raw.get()) });
+        RwLockWriteGuard { lock: self, _not_send: PhantomData }
+    }
+
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        if unsafe { pthread_rwlock_trywrlock(self.raw.get()) } == 0 {
+            Some(RwLockWriteGuard { lock: self, _not_send: PhantomData })
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        let value = unsafe { core::ptr::read(self.value.get()) };
+        let mut this = core::mem::ManuallyDrop::new(self);
+        unsafe { pthread_rwlock_destroy(this.raw.get_mut()); }
+        value
+    }
+}
+
+/// Handing out a guard when locking failed would let it alias the writer's
+/// `&mut T`
+fn check(result: c_int) {
+    match result {
+        0 => {},
+        EDEADLK => panic!("RwLock is already write-locked by this thread"),
+        error => panic!("RwLock failed to lock with error {}", error)
+    }
+}
+
+impl<T> Drop for RwLock<T> {
+    fn drop(&mut self) {
+        unsafe { pthread_rwlock_destroy(self.raw.get_mut()); }
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> RwLock<T> {
+        RwLock::new(T::default())
+    }
+}
+
+impl<'a, T> Deref for RwLockReadGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<'a, T> Drop for RwLockReadGuard<'a, T> {
+    fn drop(&mut self) {
+        unsafe { pthread_rwlock_unlock(self.lock.raw.get()); }
+    }
+}
+
+impl<'a, T> Deref for RwLockWriteGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<'a, T> DerefMut for RwLockWriteGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<'a, T> Drop for RwLockWriteGuard<'a, T> {
+    fn drop(&mut self) {
+        unsafe { pthread_rwlock_unlock(self.lock.raw.get()); }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "already write-locked")]
+    fn read_while_writing_panics() {
+        let lock = RwLock::new(0);
+        let _write = lock.write();
+        let _read = lock.read();
+    }
+}
@@ -0,0 +1,236 @@
+use crate::{
+    alloc::{capacity_overflow, handle_alloc_error, Allocator, Malloc},
+    sync::Arc
+};
+use core::{
+    alloc::Layout,
+    cell::Cell,
+    marker::PhantomData,
+    mem::{align_of, size_of},
+    ops::{Deref, Drop},
+    ptr::NonNull,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering}
+};
+
+/// Keeps the two ends' indices on separate cache lines so the threads
+/// don't fight over one
+#[repr(align(64))]
+struct CachePadded<T>(T);
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// `head` is only written by the consumer and `tail` only by the producer.
+/// Both count up forever and are masked when indexing
+struct Ring<T> {
+    buffer: NonNull<T>,
+    mask: usize,
+    head: CachePadded<AtomicUsize>,
+    tail: CachePadded<AtomicUsize>,
+    producer: AtomicBool,
+    consumer: AtomicBool
+}
+
+unsafe impl<T: Send> Send for Ring<T> {}
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+/// The writing end of a single-producer single-consumer ring. Neither end
+/// ever blocks or takes a lock, which makes it safe to use from audio
+/// callbacks and other threads that must not stall
+pub struct Producer<T> {
+    ring: Arc<Ring<T>>,
+    _not_sync: PhantomData<Cell<()>>
+}
+
+pub struct Consumer<T> {
+    ring: Arc<Ring<T>>,
+    _not_sync: PhantomData<Cell<()>>
+}
+
+/// Creates a ring that holds at least `capacity` values. The capacity is
+/// rounded up to a power of two
+pub fn channel<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+    let capacity = if capacity < 2 { 2 } else { capacity };
+    let capacity = match capacity.checked_next_power_of_two() {
+        Some(capacity) => capacity,
+        None => capacity_overflow()
+    };
+
+    let buffer = if size_of::<T>() == 0 {
+        NonNull::dangling()
+    } else {
+        let layout = match Layout::array::<T>(capacity) {
+            Ok(layout) => layout,
+            Err(_) => capacity_overflow()
+        };
+        match Malloc.allocate(layout) {
+            Some(ptr) => ptr.cast(),
+            None => handle_alloc_error(layout)
+        }
+    };
+
+    let ring = Arc::new(Ring {
+        buffer: buffer,
+        mask: capacity - 1,
+        head: CachePadded(AtomicUsize::new(0)),
+        tail: CachePadded(AtomicUsize::new(0)),
+        producer: AtomicBool::new(true),
+        consumer: AtomicBool::new(true)
+    });
+
+    let consumer = Consumer { ring: ring.clone(), _not_sync: PhantomData };
+    (Producer { ring: ring, _not_sync: PhantomData }, consumer)
+}
+
+impl<T> Ring<T> {
+    fn capacity(&self) -> usize {
+        self.mask + 1
+    }
+
+    fn len(&self) -> usize {
+        let tail = self.tail.load(Ordering::Acquire);
+        let head = self.head.load(Ordering::Acquire);
+        tail.wrapping_sub(head)
+    }
+
+    fn slot(&self, index: usize) -> *mut T {
+        if size_of::<T>() == 0 {
+            self.buffer.as_ptr()
+        } else {
+            unsafe { self.buffer.as_ptr().add(index & self.mask) }
+        }
+    }
+}
+
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        let head = *self.head.0.get_mut();
+        let tail = *self.tail.0.get_mut();
+        let mut i = head;
+        while i != tail {
+            unsafe { self.slot(i).drop_in_place(); }
+            i = i.wrapping_add(1);
+        }
+
+        if size_of::<T>() != 0 {
+            unsafe {
+                Malloc.deallocate(
+                    self.buffer.cast(),
+                    Layout::from_size_align_unchecked(
+                        self.capacity() * size_of::<T>(),
+                        align_of::<T>()
+                    )
+                );
+            }
+        }
+    }
+}
+
+impl<T> Producer<T> {
+    /// Adds a value at the back, or hands it back if the ring is full
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let ring = &*self.ring;
+        let tail = ring.tail.load(Ordering::Relaxed);
+        let head = ring.head.load(Ordering::Acquire);
+        if tail.wrapping_sub(head) == ring.capacity() {
+            return Err(value);
+        }
+
+        unsafe { ring.slot(tail).write(value); }
+        ring.tail.store(tail.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+
+    /// True once the consumer has been dropped
+    pub fn is_disconnected(&self) -> bool {
+        !self.ring.consumer.load(Ordering::Acquire)
+    }
+}
+
+impl<T> Drop for Producer<T> {
+    fn drop(&mut self) {
+        self.ring.producer.store(false, Ordering::Release);
+    }
+}
+
+impl<T> Consumer<T> {
+    /// Takes the value at the front, if there is one. Needs `&mut self` so a
+    /// reference from `peek` can't outlive the value it points at
+    ///
+    /// ```compile_fail
+    /// let (producer, mut consumer) = lstd::sync::spsc::channel::<u32>(2);
+    /// producer.push(1).unwrap();
+    /// let front = consumer.peek().unwrap();
+    /// drop(consumer.pop());
+    /// let _ = *front;
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        let ring = &*self.ring;
+        let head = ring.head.load(Ordering::Relaxed);
+        let tail = ring.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+
+        let value = unsafe { ring.slot(head).read() };
+        ring.head.store(head.wrapping_add(1), Ordering::Release);
+        Some(value)
+    }
+
+    /// Looks at the value at the front without taking it
+    pub fn peek(&self) -> Option<&T> {
+        let ring = &*self.ring;
+        let head = ring.head.load(Ordering::Relaxed);
+        let tail = ring.tail.load(Ordering::Acquire);
+        if head == tail {
+            None
+        } else {
+            unsafe { Some(&*ring.slot(head)) }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+
+    /// True once the producer has been dropped. Values it pushed before
+    /// that can still be popped
+    pub fn is_disconnected(&self) -> bool {
+        !self.ring.producer.load(Ordering::Acquire)
+    }
+}
+
+impl<T> Drop for Consumer<T> {
+    fn drop(&mut self) {
+        self.ring.consumer.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,237 @@
+mod local;
+
+pub use local::*;
+
+use crate::{
+    alloc::Box,
+    sync::Arc
+};
+use c::{
+    errno::{errno, EDEADLK, EINTR},
+    pthread::*,
+    time::{nanosleep, timespec},
+    types::{c_char, c_void}
+};
+use core::{
+    cell::UnsafeCell,
+    convert::TryFrom,
+    mem::MaybeUninit,
+    ops::Deref,
+    ptr::null_mut,
+    str::from_utf8,
+    time::Duration
+};
+
+/// Linux keeps 15 bytes of a thread's name plus the terminator
+const MAX_NAME_LEN: usize = 15;
+
+/// Where a thread leaves its result for `join`
+struct Packet<T> {
+    result: UnsafeCell<Option<T>>
+}
+
+unsafe impl<T: Send> Send for Packet<T> {}
+unsafe impl<T: Send> Sync for Packet<T> {}
+
+/// An owned permission to wait for a thread and take its result. Dropping
+/// the handle detaches the thread
+pub struct JoinHandle<T> {
+    native: pthread_t,
+    packet: Arc<Packet<T>>,
+    name: Option<ThreadName>,
+    joined: bool
+}
+
+unsafe impl<T: Send> Send for JoinHandle<T> {}
+unsafe impl<T: Send> Sync for JoinHandle<T> {}
+
+impl<T> JoinHandle<T> {
+    pub fn join(mut self) -> T {
+        match unsafe { pthread_join(self.native, null_mut()) } {
+            0 => {},
+            EDEADLK => panic!("a thread can't join itself"),
+            error => panic!("failed to join thread with error {}", error)
+        }
+        self.joined = true;
+
+        // The thread has finished, so nothing else touches the packet
+        unsafe { (*self.packet.result.get()).take().unwrap() }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl<T> Drop for JoinHandle<T> {
+    fn drop(&mut self) {
+        if !self.joined {
+            unsafe { pthread_detach(self.native); }
+        }
+    }
+}
+
+/// A thread name as the kernel stores it, truncated to fit
+#[derive(Clone, Copy)]
+pub struct ThreadName {
+    bytes: [u8; MAX_NAME_LEN + 1],
+    len: usize
+}
+
+impl ThreadName {
+    fn new(name: &str) -> ThreadName {
+        // Cut at a character boundary so the name stays valid UTF-8
+        let mut len = if name.len() > MAX_NAME_LEN { MAX_NAME_LEN } else { name.len() };
+        while !name.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        let mut bytes = [0; MAX_NAME_LEN + 1];
+        bytes[..len].copy_from_slice(&name.as_bytes()[..len]);
+        ThreadName { bytes: bytes, len: len }
+    }
+
+    fn as_ptr(&self) -> *const c_char {
+        self.bytes.as_ptr() as *const c_char
+    }
+}
+
+impl Deref for ThreadName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        from_utf8(&self.bytes[..self.len]).unwrap_or("")
+    }
+}
+
+/// Configures a thread before it is spawned
+pub struct Builder {
+    name: Option<ThreadName>,
+    stack_size: Option<usize>
+}
+
+impl Builder {
+    pub fn new() -> Builder {
+        Builder { name: None, stack_size: None }
+    }
+
+    /// Names the thread, as shown by debuggers and `top -H`. Names longer
+    /// than 15 bytes are truncated
+    pub fn name(mut self, name: &str) -> Builder {
+        self.name = Some(ThreadName::new(name));
+        self
+    }
+
+    pub fn stack_size(mut self, size: usize) -> Builder {
+        self.stack_size = Some(size);
+        self
+    }
+
+    /// Starts the thread, or returns `None` if the system can't create one
+    pub fn spawn<F, T>(self, f: F) -> Option<JoinHandle<T>>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static
+    {
+        let packet = Arc::new(Packet { result: UnsafeCell::new(None) });
+        let their_packet = packet.clone();
+        let name = self.name;
+
+        let main = move || {
+            if let Some(name) = name {
+                unsafe { pthread_setname_np(pthread_self(), name.as_ptr()); }
+            }
+
+            let result = f();
+            unsafe { *their_packet.result.get() = Some(result); }
+        };
+
+        let native = unsafe { start_thread(Box::new(main), self.stack_size)? };
+        Some(JoinHandle {
+            native: native,
+            packet: packet,
+            name: name,
+            joined: false
+        })
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder::new()
+    }
+}
+
+unsafe fn start_thread<F: FnOnce()>(
+    main: Box<F>,
+    stack_size: Option<usize>
+) -> Option<pthread_t> {
+    let mut attr = MaybeUninit::<pthread_attr_t>::uninit();
+    pthread_attr_init(attr.as_mut_ptr());
+    if let Some(size) = stack_size {
+        let size = if size < PTHREAD_STACK_MIN { PTHREAD_STACK_MIN } else { size };
+        pthread_attr_setstacksize(attr.as_mut_ptr(), size);
+    }
+
+    let arg = Box::into_raw(main);
+    let mut native = 0;
+    let rc = pthread_create(&mut native, attr.as_ptr(), thread_start::<F>, arg as *mut c_void);
+    pthread_attr_destroy(attr.as_mut_ptr());
+
+    if rc == 0 {
+        Some(native)
+    } else {
+        drop(Box::from_raw(arg));
+        None
+    }
+}
+
+extern "C" fn thread_start<F: FnOnce()>(arg: *mut c_void) -> *mut c_void {
+    // `spawn` handed over a boxed `F`
+    let main = Box::into_inner(unsafe { Box::from_raw(arg as *mut F) });
+    main();
+    null_mut()
+}
+
+/// Starts a thread running `f`. Aborts if the thread can't be created; use
+/// `Builder` to handle that or to name the thread
+pub fn spawn<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static
+{
+    match Builder::new().spawn(f) {
+        Some(handle) => handle,
+        None => crate::abort()
+    }
+}
+
+/// The name of the calling thread, which defaults to the program's name
+pub fn current_name() -> ThreadName {
+    let mut bytes = [0u8; MAX_NAME_LEN + 1];
+    unsafe {
+        pthread_getname_np(pthread_self(), bytes.as_mut_ptr() as *mut c_char, bytes.len());
+    }
+
+    let len = bytes.iter().position(|b| *b == 0).unwrap_or(MAX_NAME_LEN);
+    ThreadName { bytes: bytes, len: len }
+}
+
+/// Suspends the calling thread for at least `duration`
+pub fn sleep(duration: Duration) {
+    // Longer than that is forever anyway, and would wrap negative
+    let mut request = timespec {
+        tv_sec: i64::try_from(duration.as_secs()).unwrap_or(i64::MAX),
+        tv_nsec: duration.subsec_nanos() as i64
+    };
+
+    // An interrupted sleep reports how much time was left
+    let mut remaining = request;
+    while unsafe { nanosleep(&request, &mut remaining) } != 0 && errno() == EINTR {
+        request = remaining;
+    }
+}
+
+pub fn yield_now() {
+    unsafe { sched_yield(); }
+}
@@ -0,0 +1,113 @@
+use crate::alloc::Box;
+use c::{pthread::*, types::c_void};
+use core::{
+    ptr::dangling_mut,
+    sync::atomic::{AtomicUsize, Ordering}
+};
+
+/// A per-thread value declared with `thread_local!`. Each thread builds its
+/// own copy on first use and drops it when the thread exits; the main
+/// thread's copy is never dropped. Using a key from another value's
+/// destructor after its own value is gone panics
+pub struct LocalKey<T: 'static> {
+    // The pthread key plus one, so that zero can mean "not created yet"
+    key: AtomicUsize,
+    init: fn() -> T
+}
+
+unsafe impl<T: 'static> Sync for LocalKey<T> {}
+
+/// What a slot holds once its value is dropped. glibc clears the slot
+/// before calling the destructor, so without this a later `with` would
+/// build a new value that is never dropped
+const DESTROYED: *mut c_void = dangling_mut();
+
+/// A value along with its key, so the destructor can mark the slot
+struct Slot<T> {
+    key: pthread_key_t,
+    value: T
+}
+
+impl<T: 'static> LocalKey<T> {
+    #[doc(hidden)]
+    pub const fn new(init: fn() -> T) -> LocalKey<T> {
+        LocalKey { key: AtomicUsize::new(0), init: init }
+    }
+
+    pub fn with<R, F: FnOnce(&T) -> R>(&'static self, f: F) -> R {
+        let key = self.key();
+        unsafe {
+            let mut ptr = pthread_getspecific(key);
+            if ptr == DESTROYED {
+                panic!("thread local value used after it was destroyed");
+            }
+            if ptr.is_null() {
+                let slot = Box::new(Slot { key: key, value: (self.init)() });
+                ptr = Box::into_raw(slot) as *mut c_void;
+                pthread_setspecific(key, ptr);
+            }
+
+            f(&(*(ptr as *mut Slot<T>)).value)
+        }
+    }
+
+    fn key(&self) -> pthread_key_t {
+        let key = self.key.load(Ordering::Acquire);
+        if key != 0 { return (key - 1) as pthread_key_t; }
+
+        let mut new_key = 0;
+        if unsafe { pthread_key_create(&mut new_key, Some(destroy::<T>)) } != 0 {
+            crate::abort();
+        }
+
+        // Another thread may have created a key in the meantime, in which
+        // case ours goes back
+        match self.key.compare_exchange(
+            0,
+            new_key as usize + 1,
+            Ordering::AcqRel,
+            Ordering::Acquire
+        ) {
+            Ok(_) => new_key,
+            Err(key) => {
+                unsafe { pthread_key_delete(new_key); }
+                (key - 1) as pthread_key_t
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn destroy<T>(ptr: *mut c_void) {
+    // glibc calls destructors again for slots left non-null, as this one
+    // is once marked
+    if ptr == DESTROYED { return; }
+
+    let slot = Box::from_raw(ptr as *mut Slot<T>);
+    pthread_setspecific(slot.key, DESTROYED);
+    drop(slot);
+}
+
+/// Declares per-thread statics that are accessed through `LocalKey::with`
+///
+/// ```ignore
+/// thread_local! {
+///     static SCRATCH: RefCell<Array<u8>> = RefCell::new(Array::new());
+/// }
+///
+/// SCRATCH.with(|s| s.borrow_mut().clear());
+/// ```
+#[macro_export]
+macro_rules! thread_local {
+    () => {};
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr; $($rest:tt)*) => {
+        $crate::thread_local!($(#[$attr])* $vis static $name: $t = $init);
+        $crate::thread_local!($($rest)*);
+    };
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr) => {
+        $(#[$attr])*
+        $vis static $name: $crate::thread::LocalKey<$t> = {
+            fn init() -> $t { $init }
+            $crate::thread::LocalKey::new(init)
+        };
+    };
+}
@@ -0,0 +1,244 @@
+use crate::error::Error;
+use c::{
+    errno::EINTR,
+    time::{
+        clock_gettime, clock_nanosleep, clockid_t, timespec,
+        CLOCK_MONOTONIC, CLOCK_REALTIME, TIMER_ABSTIME
+    }
+};
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    mem::MaybeUninit,
+    ops::{Add, AddAssign, Sub, SubAssign},
+    ptr::null_mut
+};
+
+pub use core::time::Duration;
+pub use crate::thread::sleep;
+
+/// A point on the monotonic clock, for measuring how long things take. It
+/// never goes backwards, but has no meaning outside this process
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant {
+    since_boot: Duration
+}
+
+/// A point on the wall clock. It follows changes to the system time, so
+/// one taken later can still come out earlier
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SystemTime {
+    since_epoch: Duration
+}
+
+pub const UNIX_EPOCH: SystemTime = SystemTime { since_epoch: Duration::from_secs(0) };
+
+/// `SystemTime::duration_since` was given a later time. Holds how much
+/// later it was
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SystemTimeError(Duration);
+
+/// Measures time across any number of start and stop calls
+#[derive(Clone, Copy, Debug)]
+pub struct Stopwatch {
+    started: Option<Instant>,
+    elapsed: Duration
+}
+
+fn now(clock: clockid_t) -> Duration {
+    let now = unsafe {
+        let mut now = MaybeUninit::<timespec>::uninit();
+        clock_gettime(clock, now.as_mut_ptr());
+        now.assume_init()
+    };
+    Duration::new(now.tv_sec as u64, now.tv_nsec as u32)
+}
+
+impl Instant {
+    pub fn now() -> Instant {
+        Instant { since_boot: now(CLOCK_MONOTONIC) }
+    }
+
+    /// Time passed since `earlier`, or zero if it is actually later
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        self.checked_duration_since(earlier).unwrap_or_default()
+    }
+
+    pub fn checked_duration_since(&self, earlier: Instant) -> Option<Duration> {
+        self.since_boot.checked_sub(earlier.since_boot)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        Instant::now().duration_since(*self)
+    }
+
+    pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+        self.since_boot.checked_add(duration).map(|t| Instant { since_boot: t })
+    }
+
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        self.since_boot.checked_sub(duration).map(|t| Instant { since_boot: t })
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, duration: Duration) -> Instant {
+        self.checked_add(duration).expect("overflow when adding duration to instant")
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    fn sub(self, duration: Duration) -> Instant {
+        self.checked_sub(duration).expect("overflow when subtracting duration from instant")
+    }
+}
+
+impl SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+/// Same as `duration_since`, so it saturates at zero
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, earlier: Instant) -> Duration {
+        self.duration_since(earlier)
+    }
+}
+
+impl SystemTime {
+    pub fn now() -> SystemTime {
+        SystemTime { since_epoch: now(CLOCK_REALTIME) }
+    }
+
+    pub fn duration_since(&self, earlier: SystemTime) -> Result<Duration, SystemTimeError> {
+        match self.since_epoch.checked_sub(earlier.since_epoch) {
+            Some(duration) => Ok(duration),
+            None => Err(SystemTimeError(earlier.since_epoch - self.since_epoch))
+        }
+    }
+
+    /// Fails if the clock has been set back since
+    pub fn elapsed(&self) -> Result<Duration, SystemTimeError> {
+        SystemTime::now().duration_since(*self)
+    }
+
+    pub fn checked_add(&self, duration: Duration) -> Option<SystemTime> {
+        self.since_epoch.checked_add(duration).map(|t| SystemTime { since_epoch: t })
+    }
+
+    pub fn checked_sub(&self, duration: Duration) -> Option<SystemTime> {
+        self.since_epoch.checked_sub(duration).map(|t| SystemTime { since_epoch: t })
+    }
+}
+
+impl Add<Duration> for SystemTime {
+    type Output = SystemTime;
+
+    fn add(self, duration: Duration) -> SystemTime {
+        self.checked_add(duration).expect("overflow when adding duration to system time")
+    }
+}
+
+impl Sub<Duration> for SystemTime {
+    type Output = SystemTime;
+
+    fn sub(self, duration: Duration) -> SystemTime {
+        self.checked_sub(duration).expect("overflow when subtracting duration from system time")
+    }
+}
+
+impl SystemTimeError {
+    pub fn duration(&self) -> Duration {
+        self.0
+    }
+}
+
+impl Display for SystemTimeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "second time is {:?} later than the first", self.0)
+    }
+}
+
+impl Error for SystemTimeError {}
+
+/// Suspends the calling thread until `deadline`. Sleeping to an absolute
+/// time doesn't drift the way repeated `sleep` calls do
+pub fn sleep_until(deadline: Instant) {
+    let request = timespec {
+        tv_sec: deadline.since_boot.as_secs() as i64,
+        tv_nsec: deadline.since_boot.subsec_nanos() as i64
+    };
+    while unsafe { clock_nanosleep(CLOCK_MONOTONIC, TIMER_ABSTIME, &request, null_mut()) } == EINTR {}
+}
+
+impl Stopwatch {
+    /// A stopped stopwatch at zero
+    pub const fn new() -> Stopwatch {
+        Stopwatch {
+            started: None,
+            elapsed: Duration::from_secs(0)
+        }
+    }
+
+    pub fn start_new() -> Stopwatch {
+        let mut stopwatch = Stopwatch::new();
+        stopwatch.start();
+        stopwatch
+    }
+
+    /// Does nothing if it is already running
+    pub fn start(&mut self) {
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+        }
+    }
+
+    /// Does nothing if it is already stopped
+    pub fn stop(&mut self) {
+        if let Some(started) = self.started.take() {
+            self.elapsed += started.elapsed();
+        }
+    }
+
+    /// Stops and goes back to zero
+    pub fn reset(&mut self) {
+        self.started = None;
+        self.elapsed = Duration::from_secs(0);
+    }
+
+    /// Goes back to zero and keeps running, handing back the time up to now
+    pub fn restart(&mut self) -> Duration {
+        let now = Instant::now();
+        let elapsed = self.elapsed + self.started.map_or(Duration::from_secs(0), |s| now - s);
+        self.started = Some(now);
+        self.elapsed = Duration::from_secs(0);
+        elapsed
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Total time spent running, including the current run
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed + self.started.map_or(Duration::from_secs(0), |s| s.elapsed())
+    }
+}
+
+impl Default for Stopwatch {
+    fn default() -> Stopwatch {
+        Stopwatch::new()
+    }
+}
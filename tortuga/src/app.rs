@@ -0,0 +1,146 @@
+use crate::{
+    render::Context,
+    time::FrameTimer,
+    window::create_window
+};
+use lstd::time::{sleep_until, Duration, Instant};
+
+/// A game or tool driven by `Runner`. Every method but `init` has an empty
+/// default, so an application only writes the ones it needs
+pub trait Application {
+    /// Returned from `init`. Errors from creating the window and context
+    /// are converted into it too
+    type Error: From<crate::Error>;
+
+    /// Called once the window and context exist, before the first frame
+    fn init(&mut self, context: &Context) -> Result<(), Self::Error>;
+
+    /// Advances the simulation by exactly `step`. Called zero or more times
+    /// a frame so game logic sees the same step at any frame rate
+    fn fixed_update(&mut self, _step: Duration) {}
+
+    /// Called once a frame after the fixed updates, for work that follows
+    /// the real frame time
+    fn update(&mut self, _timer: &FrameTimer) {}
+
+    /// Draws a frame. `alpha` is how far, from 0 to 1, the real time has
+    /// got towards the next fixed update, for interpolating between the
+    /// last two simulation states
+    fn render(&mut self, _context: &Context, _alpha: f32) {}
+
+    fn on_resize(&mut self, _width: u16, _height: u16) {}
+
+    /// Called after the window is closed, while the context still exists
+    fn shutdown(&mut self) {}
+}
+
+/// Owns the window and render context and runs an `Application` on a
+/// fixed timestep
+pub struct Runner<'a> {
+    title: &'a str,
+    width: u16,
+    height: u16,
+    validation: bool,
+    step: Duration,
+    frame_time: Option<Duration>,
+    max_frame_time: Duration
+}
+
+impl<'a> Runner<'a> {
+    /// A 640x480 window updating 60 times a second, with no frame cap
+    pub fn new(title: &'a str) -> Runner<'a> {
+        Runner {
+            title: title,
+            width: 640,
+            height: 480,
+            validation: false,
+            step: Duration::from_secs(1) / 60,
+            frame_time: None,
+            max_frame_time: Duration::from_millis(250)
+        }
+    }
+
+    pub fn size(mut self, width: u16, height: u16) -> Runner<'a> {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Enables the Vulkan validation layers
+    pub fn validation(mut self, validation: bool) -> Runner<'a> {
+        self.validation = validation;
+        self
+    }
+
+    /// How many times a second `fixed_update` runs
+    pub fn update_rate(mut self, hz: u32) -> Runner<'a> {
+        assert!(hz > 0, "update rate must be above zero");
+        self.step = Duration::from_secs(1) / hz;
+        self
+    }
+
+    /// Sleeps out the rest of each frame to stay at or under `fps`. Zero
+    /// takes the cap off
+    pub fn frame_limit(mut self, fps: u32) -> Runner<'a> {
+        self.frame_time = if fps > 0 { Some(Duration::from_secs(1) / fps) } else { None };
+        self
+    }
+
+    /// Frames longer than this, as after a breakpoint or a dragged window,
+    /// are simulated as if they took only this long. Otherwise catching up
+    /// would make the next frame even longer, and so on
+    pub fn max_frame_time(mut self, max_frame_time: Duration) -> Runner<'a> {
+        self.max_frame_time = max_frame_time;
+        self
+    }
+
+    /// Runs `app` until its window is closed
+    pub fn run<A: Application>(self, app: &mut A) -> Result<(), A::Error> {
+        let window = create_window(self.title, self.width, self.height)?;
+        let context = Context::new(&*window, self.validation)?;
+        app.init(&context)?;
+
+        let mut size = window.size();
+        let mut timer = FrameTimer::new();
+        let mut accumulator = Duration::from_secs(0);
+        let mut next_frame = Instant::now();
+        timer.tick();
+
+        loop {
+            window.update();
+            if window.should_close() {
+                break;
+            }
+
+            if window.size() != size {
+                size = window.size();
+                app.on_resize(size.0, size.1);
+            }
+
+            accumulator += timer.tick().min(self.max_frame_time);
+            while accumulator >= self.step {
+                app.fixed_update(self.step);
+                accumulator -= self.step;
+            }
+            let alpha = accumulator.as_secs_f32() / self.step.as_secs_f32();
+
+            app.update(&timer);
+            app.render(&context, alpha);
+
+            if let Some(frame_time) = self.frame_time {
+                // Aim for evenly spaced frames rather than a fixed sleep,
+                // but don't try to make up for frames that ran long
+                next_frame += frame_time;
+                let now = Instant::now();
+                if next_frame > now {
+                    sleep_until(next_frame);
+                } else {
+                    next_frame = now;
+                }
+            }
+        }
+
+        app.shutdown();
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use lstd::error::Error as StdError;
+
+#[cfg(feature = "vulkan")]
+use dl::DlError;
+#[cfg(feature = "vulkan")]
+use vulkan_h::*;
+
+/// Everything that can go wrong while setting up a window or renderer
+#[derive(Debug)]
+pub enum Error {
+    /// The window title contained a NUL
+    InvalidTitle,
+    /// The X server couldn't be reached. Holds the code from
+    /// `xcb_connection_has_error`
+    #[cfg(target_os = "linux")]
+    XcbConnection(c::types::c_int),
+    /// Interning the named atom failed
+    #[cfg(target_os = "linux")]
+    XcbAtom(&'static str),
+    /// libvulkan couldn't be loaded
+    #[cfg(feature = "vulkan")]
+    Library(DlError),
+    /// The loader or driver doesn't provide the named function
+    #[cfg(feature = "vulkan")]
+    MissingFunction(&'static str),
+    /// The named Vulkan call returned something other than `VK_SUCCESS`
+    #[cfg(feature = "vulkan")]
+    Vulkan(&'static str, VkResult),
+    NoPhysicalDevices,
+    /// The device has no queues that can draw or present to the surface
+    NoSuitableQueue
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[cfg(feature = "vulkan")]
+impl From<DlError> for Error {
+    fn from(error: DlError) -> Error {
+        Error::Library(error)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Error::InvalidTitle =>
+                f.write_str("window title contains a NUL"),
+            #[cfg(target_os = "linux")]
+            Error::XcbConnection(code) =>
+                write!(f, "could not connect to the X server (xcb error {})", code),
+            #[cfg(target_os = "linux")]
+            Error::XcbAtom(name) =>
+                write!(f, "could not intern the {} atom", name),
+            #[cfg(feature = "vulkan")]
+            Error::Library(_) =>
+                f.write_str("could not load libvulkan"),
+            #[cfg(feature = "vulkan")]
+            Error::MissingFunction(name) =>
+                write!(f, "{} is not available", name),
+            #[cfg(feature = "vulkan")]
+            Error::Vulkan(call, result) => match result_name(*result) {
+                Some(name) => write!(f, "{} failed with {}", call, name),
+                None => write!(f, "{} failed with VkResult {}", call, result)
+            },
+            Error::NoPhysicalDevices =>
+                f.write_str("no Vulkan devices found"),
+            Error::NoSuitableQueue =>
+                f.write_str("no queue family can draw, or none can present to the surface")
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            #[cfg(feature = "vulkan")]
+            Error::Library(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+#[cfg(feature = "vulkan")]
+fn result_name(result: VkResult) -> Option<&'static str> {
+    Some(match result {
+        VK_ERROR_OUT_OF_HOST_MEMORY      => "VK_ERROR_OUT_OF_HOST_MEMORY",
+        VK_ERROR_OUT_OF_DEVICE_MEMORY    => "VK_ERROR_OUT_OF_DEVICE_MEMORY",
+        VK_ERROR_INITIALIZATION_FAILED   => "VK_ERROR_INITIALIZATION_FAILED",
+        VK_ERROR_DEVICE_LOST             => "VK_ERROR_DEVICE_LOST",
+        VK_ERROR_LAYER_NOT_PRESENT       => "VK_ERROR_LAYER_NOT_PRESENT",
+        VK_ERROR_EXTENSION_NOT_PRESENT   => "VK_ERROR_EXTENSION_NOT_PRESENT",
+        VK_ERROR_FEATURE_NOT_PRESENT     => "VK_ERROR_FEATURE_NOT_PRESENT",
+        VK_ERROR_INCOMPATIBLE_DRIVER     => "VK_ERROR_INCOMPATIBLE_DRIVER",
+        VK_ERROR_TOO_MANY_OBJECTS        => "VK_ERROR_TOO_MANY_OBJECTS",
+        VK_ERROR_SURFACE_LOST_KHR        => "VK_ERROR_SURFACE_LOST_KHR",
+        VK_ERROR_NATIVE_WINDOW_IN_USE_KHR => "VK_ERROR_NATIVE_WINDOW_IN_USE_KHR",
+        _ => return None
+    })
+}
@@ -1,5 +1,13 @@
 #![no_std]
 
+pub mod app;
+
+pub mod error;
+
 pub mod window;
 
 pub mod render;
+
+pub mod time;
+
+pub use error::{Error, Result};
@@ -5,4 +5,6 @@ pub mod context;
 mod vulkan;
 
 pub use context::Context;
+#[cfg(feature = "vulkan")]
+pub use vulkan::{Device, PhysicalDevice};
 // pub use device::Device;
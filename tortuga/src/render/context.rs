@@ -5,7 +5,9 @@ use crate::{
         PhysicalDevice,
         Device
     },
-    window::Window
+    window::Window,
+    Error,
+    Result
 };
 use c::types::*;
 use core::{
@@ -23,70 +25,74 @@ pub struct Context {
 }
 
 impl Context {
-    pub fn new(window: &dyn Window) -> Option<Context> {
+    /// `validation` turns on the Vulkan validation layers
+    pub fn new(window: &dyn Window, validation: bool) -> Result<Context> {
         let extensions = [
             VK_KHR_SURFACE_EXTENSION_NAME.as_ptr() as *const c_char,
             VK_KHR_XCB_SURFACE_EXTENSION_NAME.as_ptr() as *const c_char,
         ];
 
-        let instance = Instance::new(&extensions)?;
+        let instance = Instance::new(&extensions, validation)?;
         let instance = Rc::new(RefCell::new(instance));
         let instance = ManuallyDrop::new(instance);
 
         let surface = Surface::new(&instance, window)?;
         let surface = Rc::new(RefCell::new(surface));
         let surface = ManuallyDrop::new(surface);
-        Some(Context {
+        Ok(Context {
             instance: instance,
             surface: surface
         })
     }
 
-    pub fn get_physical_devices(&self) -> Option<Array<PhysicalDevice>> {
-        let enumerate_devices =
-            self.instance.borrow().enumerate_physical_devices;
+    /// Fails with `NoPhysicalDevices` rather than returning an empty list
+    pub fn get_physical_devices(&self) -> Result<Array<PhysicalDevice>> {
+        let enumerate_devices = self.instance.borrow()
+            .enumerate_physical_devices
+            .ok_or(Error::MissingFunction("vkEnumeratePhysicalDevices"))?;
 
         let mut n_devices = unsafe {
             let mut n_devices = MaybeUninit::uninit();
-            let result = enumerate_devices?(
+            let result = enumerate_devices(
                 self.instance.borrow().instance,
                 n_devices.as_mut_ptr(),
                 null_mut()
             );
-            if result != VK_SUCCESS { return None; }
+            if result != VK_SUCCESS {
+                return Err(Error::Vulkan("vkEnumeratePhysicalDevices", result));
+            }
 
             let n_devices = n_devices.assume_init();
-            if n_devices == 0 { return None; }
+            if n_devices == 0 { return Err(Error::NoPhysicalDevices); }
             n_devices
         };
 
-        let mut vk_devices = Array::new(n_devices as usize);
+        let mut vk_devices = Array::with_capacity(n_devices as usize);
         let result = unsafe {
-            let result = enumerate_devices?(
+            let result = enumerate_devices(
                 self.instance.borrow().instance,
                 &mut n_devices as *mut _,
                 vk_devices.as_mut_ptr()
             );
-            vk_devices.assume_init(n_devices as isize);
+            vk_devices.set_len(n_devices as usize);
             result
         };
-        if result != VK_SUCCESS { return None; }
-
-        let mut physical_devices = Array::new(n_devices as usize);
-        for device in vk_devices.iter() {
-            physical_devices.push(PhysicalDevice::new(
-                &self.instance,
-                *device
-            ));
+        if result != VK_SUCCESS {
+            return Err(Error::Vulkan("vkEnumeratePhysicalDevices", result));
         }
 
-        Some(physical_devices)
+        let physical_devices: Array<PhysicalDevice> = vk_devices
+            .iter()
+            .map(|device| PhysicalDevice::new(&self.instance, *device))
+            .collect();
+
+        Ok(physical_devices)
     }
 
     pub fn create_device(
         &self,
         physical_device: &PhysicalDevice
-    ) -> Option<Device> {
+    ) -> Result<Device> {
         Device::new(&self.instance, &self.surface, physical_device)
     }
 }
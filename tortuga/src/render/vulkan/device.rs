@@ -1,7 +1,11 @@
-use crate::render::vulkan::{
-    instance::Instance,
-    surface::Surface,
-    physical_device::PhysicalDevice
+use crate::{
+    render::vulkan::{
+        instance::Instance,
+        surface::Surface,
+        physical_device::PhysicalDevice
+    },
+    Error,
+    Result
 };
 use c::types::*;
 use core::{
@@ -52,7 +56,7 @@ impl Device {
         instance: &Rc<RefCell<Instance>>,
         surface: &Rc<RefCell<Surface>>,
         physical_device: &PhysicalDevice
-    ) -> Option<Device> {
+    ) -> Result<Device> {
         let extensions = [
             VK_KHR_SWAPCHAIN_EXTENSION_NAME.as_ptr() as *const c_char
         ];
@@ -102,7 +106,7 @@ impl Device {
             vk_cmd_draw_indexed
         ) = load_device_functions(&instance.borrow(), &device)?;
 
-        Some(Device {
+        Ok(Device {
             instance: Rc::clone(instance),
             device: device,
             graphics_index: graphics_index,
@@ -157,7 +161,7 @@ fn create_device(
     extensions: &[*const c_char],
     graphics_index: u32,
     present_index: u32,
-) -> Option<VkDevice> {
+) -> Result<VkDevice> {
     const priority: f32 = 1.0;
     let queue_infos = [
         VkDeviceQueueCreateInfo {
@@ -201,25 +205,36 @@ fn create_device(
 
     let device = unsafe {
         let mut device = MaybeUninit::uninit();
-        let result = instance.create_device?(
+        let create_device = instance.create_device
+            .ok_or(Error::MissingFunction("vkCreateDevice"))?;
+        let result = create_device(
             physical_device.device,
             &create_info as *const _,
             null_mut(),
             device.as_mut_ptr()
         );
-        if result != VK_SUCCESS { return None; }
+        if result != VK_SUCCESS {
+            return Err(Error::Vulkan("vkCreateDevice", result));
+        }
 
         device.assume_init()
     };
 
-    Some(device)
+    Ok(device)
 }
 
 fn get_queue_information(
     instance: &Rc<RefCell<Instance>>,
     surface: &Rc<RefCell<Surface>>,
     device: &PhysicalDevice
-) -> Option<(u32, u32)> {
+) -> Result<(u32, u32)> {
+    let get_queue_family_properties = instance.borrow()
+        .get_physical_device_queue_family_properties
+        .ok_or(Error::MissingFunction("vkGetPhysicalDeviceQueueFamilyProperties"))?;
+    let get_surface_support = instance.borrow()
+        .get_physical_device_surface_support
+        .ok_or(Error::MissingFunction("vkGetPhysicalDeviceSurfaceSupportKHR"))?;
+
     let mut graphics_set = false;
     let mut present_set = false;
     let mut graphics_index = 0_u32;
@@ -227,23 +242,23 @@ fn get_queue_information(
 
     let n_props = unsafe {
         let mut n_props = MaybeUninit::uninit();
-        instance.borrow().get_physical_device_queue_family_properties?(
+        get_queue_family_properties(
             device.device as *const _ as *mut _,
             n_props.as_mut_ptr(),
             null_mut()
         );
         n_props.assume_init()
     };
-    if n_props == 0 { return None; }
+    if n_props == 0 { return Err(Error::NoSuitableQueue); }
     let mut props: Array<VkQueueFamilyProperties> =
-        Array::new(n_props as usize);
+        Array::with_capacity(n_props as usize);
     unsafe {
-        instance.borrow().get_physical_device_queue_family_properties?(
+        get_queue_family_properties(
             device.device as *const _ as *mut _,
             &n_props as *const _ as *mut _,
             props.as_mut_ptr()
         );
-        props.assume_init(n_props as isize);
+        props.set_len(n_props as usize);
     }
 
     for (i, prop) in props.iter().enumerate() {
@@ -256,14 +271,16 @@ fn get_queue_information(
 
         let mut present_support = 0_u32;
         let result = unsafe {
-            instance.borrow().get_physical_device_surface_support?(
+            get_surface_support(
                 device.device,
                 i as u32,
                 surface.borrow().surface,
                 &mut present_support as *mut _
             )
         };
-        if result != VK_SUCCESS { return None; }
+        if result != VK_SUCCESS {
+            return Err(Error::Vulkan("vkGetPhysicalDeviceSurfaceSupportKHR", result));
+        }
 
         if prop.queueCount > 0 && present_support != 0 {
             present_set = true;
@@ -272,16 +289,16 @@ fn get_queue_information(
     }
 
     if !graphics_set || !present_set {
-        None
+        Err(Error::NoSuitableQueue)
     } else {
-        Some((graphics_index, present_index))
+        Ok((graphics_index, present_index))
     }
 }
 
 fn load_device_functions(
     instance: &Instance,
     device: &VkDevice
-) -> Option<(
+) -> Result<(
     PFN_vkGetDeviceQueue,
     PFN_vkCreateSemaphore,
     PFN_vkDestroySemaphore,
@@ -318,12 +335,12 @@ fn load_device_functions(
                 $type
             ) {
                 Some(ref f) => Some(*f),
-                None => return None
+                None => return Err(Error::MissingFunction(&stringify!($type)[4..]))
             }
         }}
     }
 
-    Some((
+    Ok((
         load!(PFN_vkGetDeviceQueue),
         load!(PFN_vkCreateSemaphore),
         load!(PFN_vkDestroySemaphore),
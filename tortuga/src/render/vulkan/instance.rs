@@ -1,6 +1,10 @@
+use crate::{Error, Result};
 use c::types::*;
 use dl::SharedLibrary;
-use lstd::prelude::*;
+use lstd::{
+    log::Level,
+    prelude::*
+};
 use vulkan_h::*;
 use core::{
     cell::RefCell,
@@ -24,12 +28,18 @@ pub struct Instance {
     pub create_device: PFN_vkCreateDevice,
     pub destroy_device: PFN_vkDestroyDevice,
     pub get_device_proc_addr: PFN_vkGetDeviceProcAddr,
+    // Null without validation, or when VK_EXT_debug_utils is missing
+    debug_messenger: VkDebugUtilsMessengerEXT,
+    destroy_debug_messenger: PFN_vkDestroyDebugUtilsMessengerEXT
 }
 
 impl Instance {
-    pub fn new(extensions: &[*const c_char]) -> Option<Instance> {
+    /// With `validation`, the validation layers are enabled and report
+    /// through `lstd::log` under the `vulkan` target
+    pub fn new(extensions: &[*const c_char], validation: bool) -> Result<Instance> {
         let libvulkan = SharedLibrary::open(cstr!("libvulkan.so"))?;
-        let symbol = libvulkan.symbol(cstr!("vkGetInstanceProcAddr"))?;
+        let symbol = libvulkan.symbol(cstr!("vkGetInstanceProcAddr"))
+            .map_err(|_| Error::MissingFunction("vkGetInstanceProcAddr"))?;
         let get_instance_proc_addr = unsafe {
             type T = PFN_vkGetInstanceProcAddr;
             transmute::<*mut c_void, T>(symbol)
@@ -40,7 +50,8 @@ impl Instance {
 
         let instance = create_instance(
             vk_create_instance,
-            &extensions
+            &extensions,
+            validation
         )?;
 
         let (
@@ -59,7 +70,13 @@ impl Instance {
             get_instance_proc_addr
         )?;
 
-        Some(Instance {
+        let (debug_messenger, vk_destroy_debug_messenger) = if validation {
+            create_debug_messenger(instance, get_instance_proc_addr)
+        } else {
+            (null_mut(), None)
+        };
+
+        Ok(Instance {
             libvulkan: Rc::new(RefCell::new(libvulkan)),
             instance: instance,
             get_instance_proc_addr: get_instance_proc_addr,
@@ -73,13 +90,24 @@ impl Instance {
             get_physical_device_surface_support: vk_get_physical_device_surface_support,
             create_device: vk_create_device,
             destroy_device: vk_destroy_device,
-            get_device_proc_addr: vk_get_device_proc_addr
+            get_device_proc_addr: vk_get_device_proc_addr,
+            debug_messenger: debug_messenger,
+            destroy_debug_messenger: vk_destroy_debug_messenger
         })
     }
 }
 
 impl Drop for Instance {
     fn drop(&mut self) {
+        if let Some(f) = self.destroy_debug_messenger {
+            unsafe {
+                f(
+                    self.instance,
+                    self.debug_messenger,
+                    null_mut()
+                );
+            }
+        }
         if let Some(f) = self.destroy_instance {
             unsafe {
                 f(
@@ -93,22 +121,23 @@ impl Drop for Instance {
 
 fn create_instance(
     vk_create_instance: PFN_vkCreateInstance,
-    extensions: &[*const c_char]
-) -> Option<VkInstance> {
-    let layers = {
-        #[cfg(not(debug_assertions))]
-        let layers = [];
-
-        #[cfg(debug_assertions)]
-        let layers = [
-            cstr!("VK_LAYER_LUNARG_standard_validation")
-        ];
-
-        layers
+    extensions: &[*const c_char],
+    validation: bool
+) -> Result<VkInstance> {
+    let layers: &[*const c_char] = if validation {
+        &[cstr!("VK_LAYER_LUNARG_standard_validation").as_ptr()]
+    } else {
+        &[]
     };
 
-    let app_name = cstr!("Tortuga");
-    let engine_name = cstr!("Tortuga");
+    // Validation messages are delivered through VK_EXT_debug_utils
+    let mut extensions = Array::from_slice(extensions);
+    if validation {
+        extensions.push(VK_EXT_DEBUG_UTILS_EXTENSION_NAME.as_ptr() as *const c_char);
+    }
+
+    let app_name = cstr!("Tortuga").as_ptr();
+    let engine_name = cstr!("Tortuga").as_ptr();
     let app_info = VkApplicationInfo {
         sType: VK_STRUCTURE_TYPE_APPLICATION_INFO,
         pNext: null(),
@@ -137,31 +166,35 @@ fn create_instance(
         }
     };
 
-    Some(unsafe {
+    let vk_create_instance =
+        vk_create_instance.ok_or(Error::MissingFunction("vkCreateInstance"))?;
+    Ok(unsafe {
         let mut instance: MaybeUninit<VkInstance> = MaybeUninit::uninit();
-        let result = vk_create_instance?(
+        let result = vk_create_instance(
             &create_info as *const _,
             null_mut(),
             instance.as_mut_ptr()
         );
-        if result != VK_SUCCESS { return None; }
+        if result != VK_SUCCESS {
+            return Err(Error::Vulkan("vkCreateInstance", result));
+        }
         instance.assume_init()
     })
 }
 
 fn load_preinstance_functions(
     f: PFN_vkGetInstanceProcAddr
-) -> Option<PFN_vkCreateInstance> {
+) -> Result<PFN_vkCreateInstance> {
     macro_rules! load {
         ($type:ident) => {{
             match load_vulkan_function!(null_mut(), f, $type) {
                 Some(ref f) => Some(*f),
-                None => return None
+                None => return Err(Error::MissingFunction(&stringify!($type)[4..]))
             }
         }}
     }
 
-    Some(
+    Ok(
         load!(PFN_vkCreateInstance)
     )
 }
@@ -169,7 +202,7 @@ fn load_preinstance_functions(
 fn load_instance_functions(
     instance: &VkInstance,
     f: PFN_vkGetInstanceProcAddr
-) -> Option<(
+) -> Result<(
     PFN_vkDestroyInstance,
     PFN_vkCreateXcbSurfaceKHR,
     PFN_vkDestroySurfaceKHR,
@@ -185,12 +218,12 @@ fn load_instance_functions(
         ($type:ident) => {{
             match load_vulkan_function!(*instance, f, $type) {
                 Some(ref f) => Some(*f),
-                None => return None
+                None => return Err(Error::MissingFunction(&stringify!($type)[4..]))
             }
         }}
     }
 
-    Some((
+    Ok((
         load!(PFN_vkDestroyInstance),
         load!(PFN_vkCreateXcbSurfaceKHR),
         load!(PFN_vkDestroySurfaceKHR),
@@ -204,3 +237,89 @@ fn load_instance_functions(
     ))
 }
 
+/// Routes validation layer messages into the log. Failing to set this up
+/// only costs the messages, so it is logged rather than returned
+fn create_debug_messenger(
+    instance: VkInstance,
+    f: PFN_vkGetInstanceProcAddr
+) -> (VkDebugUtilsMessengerEXT, PFN_vkDestroyDebugUtilsMessengerEXT) {
+    let create: PFN_vkCreateDebugUtilsMessengerEXT =
+        load_vulkan_function!(instance, f, PFN_vkCreateDebugUtilsMessengerEXT);
+    let destroy: PFN_vkDestroyDebugUtilsMessengerEXT =
+        load_vulkan_function!(instance, f, PFN_vkDestroyDebugUtilsMessengerEXT);
+    let create = match (create, destroy) {
+        (Some(create), Some(_)) => create,
+        _ => {
+            lstd::warn!(target: "vulkan", "VK_EXT_debug_utils is missing, validation messages are lost");
+            return (null_mut(), None);
+        }
+    };
+
+    let create_info = VkDebugUtilsMessengerCreateInfoEXT {
+        sType: VK_STRUCTURE_TYPE_DEBUG_UTILS_MESSENGER_CREATE_INFO_EXT,
+        pNext: null(),
+        flags: 0,
+        messageSeverity: (
+            VK_DEBUG_UTILS_MESSAGE_SEVERITY_VERBOSE_BIT_EXT
+            | VK_DEBUG_UTILS_MESSAGE_SEVERITY_INFO_BIT_EXT
+            | VK_DEBUG_UTILS_MESSAGE_SEVERITY_WARNING_BIT_EXT
+            | VK_DEBUG_UTILS_MESSAGE_SEVERITY_ERROR_BIT_EXT
+        ) as _,
+        messageType: (
+            VK_DEBUG_UTILS_MESSAGE_TYPE_GENERAL_BIT_EXT
+            | VK_DEBUG_UTILS_MESSAGE_TYPE_VALIDATION_BIT_EXT
+            | VK_DEBUG_UTILS_MESSAGE_TYPE_PERFORMANCE_BIT_EXT
+        ) as _,
+        pfnUserCallback: Some(debug_callback),
+        pUserData: null_mut()
+    };
+
+    unsafe {
+        let mut messenger = MaybeUninit::uninit();
+        let result = create(
+            instance,
+            &create_info as *const _,
+            null_mut(),
+            messenger.as_mut_ptr()
+        );
+        if result != VK_SUCCESS {
+            lstd::warn!(target: "vulkan", "{}", Error::Vulkan("vkCreateDebugUtilsMessengerEXT", result));
+            return (null_mut(), None);
+        }
+        (messenger.assume_init(), destroy)
+    }
+}
+
+unsafe extern "C" fn debug_callback(
+    severity: VkDebugUtilsMessageSeverityFlagBitsEXT,
+    types: VkDebugUtilsMessageTypeFlagsEXT,
+    data: *const VkDebugUtilsMessengerCallbackDataEXT,
+    _user_data: *mut c_void
+) -> VkBool32 {
+    let severity = severity as u32;
+    let level = if severity & VK_DEBUG_UTILS_MESSAGE_SEVERITY_ERROR_BIT_EXT as u32 != 0 {
+        Level::Error
+    } else if severity & VK_DEBUG_UTILS_MESSAGE_SEVERITY_WARNING_BIT_EXT as u32 != 0 {
+        Level::Warn
+    } else if severity & VK_DEBUG_UTILS_MESSAGE_SEVERITY_INFO_BIT_EXT as u32 != 0 {
+        // The loader is chatty at this level
+        Level::Debug
+    } else {
+        Level::Trace
+    };
+
+    let types = types as u32;
+    let target = if types & VK_DEBUG_UTILS_MESSAGE_TYPE_VALIDATION_BIT_EXT as u32 != 0 {
+        "vulkan::validation"
+    } else if types & VK_DEBUG_UTILS_MESSAGE_TYPE_PERFORMANCE_BIT_EXT as u32 != 0 {
+        "vulkan::performance"
+    } else {
+        "vulkan"
+    };
+
+    let message = CStr::from_ptr((*data).pMessage);
+    lstd::log!(target: target, level, "{}", message.to_str().unwrap_or("<message is not UTF-8>"));
+
+    // Returning true would make the call that triggered this fail
+    VK_FALSE
+}
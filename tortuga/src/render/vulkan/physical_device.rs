@@ -40,14 +40,11 @@ impl PhysicalDevice {
         }
     }
 
+    /// The name up to its terminator. A driver that reports one that isn't
+    /// UTF-8 gets a placeholder rather than a panic
     pub fn name(&self) -> &str {
-        core::str::from_utf8(
-            unsafe {
-                core::mem::transmute::<&[i8], &[u8]>(
-                    &self.props.deviceName
-                )
-            }
-        ).unwrap()
+        let name = unsafe { CStr::from_ptr(self.props.deviceName.as_ptr()) };
+        name.to_str().unwrap_or("<invalid name>")
     }
 
     pub fn kind(&self) -> PhysicalDeviceType {
@@ -1,6 +1,8 @@
 use crate::{
     render::vulkan::Instance,
-    window::Window
+    window::Window,
+    Error,
+    Result
 };
 use core::{
     cell::RefCell,
@@ -20,7 +22,7 @@ impl Surface {
     pub fn new(
         instance: &Rc<RefCell<Instance>>,
         window: &dyn Window
-    ) -> Option<Surface> {
+    ) -> Result<Surface> {
         let (connection, window) = window.get_os_details();
         let create_info = VkXcbSurfaceCreateInfoKHR {
             sType: VK_STRUCTURE_TYPE_XCB_SURFACE_CREATE_INFO_KHR,
@@ -30,18 +32,23 @@ impl Surface {
             window: window
         };
 
-        Some(Surface {
+        let create_xcb_surface = instance.borrow().create_xcb_surface
+            .ok_or(Error::MissingFunction("vkCreateXcbSurfaceKHR"))?;
+
+        Ok(Surface {
             instance: Rc::clone(instance),
             surface: unsafe {
                 let mut surface: MaybeUninit<VkSurfaceKHR> =
                     MaybeUninit::uninit();
-                let result = instance.borrow().create_xcb_surface?(
+                let result = create_xcb_surface(
                     instance.borrow().instance,
                     &create_info as *const _ as *mut _,
                     null_mut(),
                     surface.as_mut_ptr()
                 );
-                if result != VK_SUCCESS { return None; }
+                if result != VK_SUCCESS {
+                    return Err(Error::Vulkan("vkCreateXcbSurfaceKHR", result));
+                }
                 surface.assume_init()
             }
         })
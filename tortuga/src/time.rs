@@ -0,0 +1,97 @@
+use lstd::time::{Duration, Instant};
+
+/// How many recent frame times are kept for percentiles
+const HISTORY: usize = 240;
+
+/// Weight of the newest frame in the smoothed frame time. Lower is steadier
+/// but slower to follow real changes
+const SMOOTHING: f32 = 0.1;
+
+/// Times the main loop. Call `tick` once per frame, then read the delta
+/// time and statistics for the frame that just ended
+pub struct FrameTimer {
+    last: Option<Instant>,
+    delta: Duration,
+    smoothed: f32,
+    history: [Duration; HISTORY],
+    next: usize,
+    frames: u64
+}
+
+impl FrameTimer {
+    pub fn new() -> FrameTimer {
+        FrameTimer {
+            last: None,
+            delta: Duration::from_secs(0),
+            smoothed: 0.0,
+            history: [Duration::from_secs(0); HISTORY],
+            next: 0,
+            frames: 0
+        }
+    }
+
+    /// Marks the start of a frame and returns the time since the last one.
+    /// The first tick only starts the clock and returns zero
+    pub fn tick(&mut self) -> Duration {
+        let now = Instant::now();
+        let last = match self.last.replace(now) {
+            Some(last) => last,
+            None => return Duration::from_secs(0)
+        };
+
+        self.delta = now - last;
+        let secs = self.delta.as_secs_f32();
+        self.smoothed = if self.frames == 0 {
+            secs
+        } else {
+            self.smoothed + (secs - self.smoothed) * SMOOTHING
+        };
+
+        self.history[self.next] = self.delta;
+        self.next = (self.next + 1) % HISTORY;
+        self.frames += 1;
+        self.delta
+    }
+
+    /// Length of the last frame
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_secs(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// Frames timed so far
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Frames per second from the smoothed frame time, so it doesn't jump
+    /// around with every frame
+    pub fn fps(&self) -> f32 {
+        if self.smoothed > 0.0 { 1.0 / self.smoothed } else { 0.0 }
+    }
+
+    /// The frame time `percent` of the last few seconds of frames were no
+    /// slower than. The 99th percentile shows stutter an average hides
+    pub fn percentile(&self, percent: f32) -> Duration {
+        let count = (self.frames as usize).min(HISTORY);
+        if count == 0 {
+            return Duration::from_secs(0);
+        }
+
+        let mut sorted = self.history;
+        let sorted = &mut sorted[..count];
+        sorted.sort_unstable();
+
+        let rank = (percent.max(0.0) / 100.0 * count as f32) as usize;
+        sorted[rank.min(count - 1)]
+    }
+}
+
+impl Default for FrameTimer {
+    fn default() -> FrameTimer {
+        FrameTimer::new()
+    }
+}
@@ -1,6 +1,9 @@
 #[cfg_attr(target_os = "linux", path = "window/linux.rs")]
 mod native;
 
+use crate::Result;
+use lstd::{alloc::Box, into_dyn};
+
 #[cfg(target_os = "linux")]
 use xcb_h::{xcb_connection_t, xcb_window_t};
 
@@ -8,14 +11,18 @@ pub fn create_window(
     title: &str,
     width: u16,
     height: u16
-) -> Option<impl Window> {
-    native::create_window(title, width, height)
+) -> Result<Box<dyn Window>> {
+    let window = native::create_window(title, width, height)?;
+    Ok(into_dyn!(Box::new(window), dyn Window))
 }
 
 pub trait Window {
     fn should_close(&self) -> bool;
     fn update(&self);
 
+    /// Width and height in pixels, as of the last `update`
+    fn size(&self) -> (u16, u16);
+
     #[cfg(target_os = "linux")]
     fn get_os_details(&self) -> (*const xcb_connection_t, xcb_window_t);
 }
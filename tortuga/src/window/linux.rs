@@ -1,14 +1,15 @@
-use crate::window::Window;
-use lstd::{
-    alloc::dealloc,
-    container::Array
+use crate::{
+    window::Window,
+    Error,
+    Result
 };
+use c::stdlib::free;
+use lstd::ffi::{CStr, CString};
 use core::{
     cell::Cell,
     mem::{MaybeUninit, transmute},
     ops::Drop,
-    ptr::{null, null_mut},
-    str::from_utf8_unchecked
+    ptr::null_mut
 };
 use xcb_h::*;
 
@@ -16,21 +17,10 @@ pub fn create_window(
     title: &str,
     width: u16,
     height :u16
-) -> Option<NativeWindow> {
-    let title: Array<u8> = {
-        let mut new_title = Array::new(title.len() + 1);
-        for c in title.as_bytes().iter() {
-            new_title.push(*c);
-        }
-
-        new_title
-    };
+) -> Result<NativeWindow> {
+    let title = CString::new(title).map_err(|_| Error::InvalidTitle)?;
 
-    NativeWindow::new(
-        unsafe { from_utf8_unchecked(&title) },
-        width,
-        height
-    )
+    NativeWindow::new(&title, width, height)
 }
 
 pub struct NativeWindow {
@@ -44,10 +34,10 @@ pub struct NativeWindow {
 
 impl NativeWindow {
     pub fn new(
-        title: &str,
+        title: &CStr,
         width: u16,
         height: u16
-    ) -> Option<NativeWindow> {
+    ) -> Result<NativeWindow> {
         let (cn, screen) = NativeWindow::setup_connection()?;
         let wn = NativeWindow::setup_window(
             cn,
@@ -56,20 +46,24 @@ impl NativeWindow {
             width,
             height
         );
-        let delete_atom = NativeWindow::setup_atoms(cn, wn)?;
-        unsafe { xcb_flush(cn as *mut _); }
 
-        Some(NativeWindow {
+        // Built before the atoms are looked up so failing there still
+        // destroys the window and connection
+        let mut window = NativeWindow {
             connection: cn,
             window: wn,
-            delete_atom: delete_atom,
+            delete_atom: 0,
             width: Cell::new(width),
             height: Cell::new(height),
             should_close: Cell::new(false)
-        })
+        };
+        window.delete_atom = NativeWindow::setup_atoms(cn, wn)?;
+        unsafe { xcb_flush(cn as *mut _); }
+
+        Ok(window)
     }
 
-    fn setup_connection() -> Option<(
+    fn setup_connection() -> Result<(
         *const xcb_connection_t,
         *const xcb_screen_t
     )> {
@@ -78,7 +72,13 @@ impl NativeWindow {
             let cn = xcb_connect(null_mut(), index.as_mut_ptr());
             (cn as *const _, index.assume_init())
         };
-        if cn == null() { return None; }
+        // xcb_connect never returns null, but hands back a connection in
+        // an error state instead
+        let code = unsafe { xcb_connection_has_error(cn as *mut _) };
+        if code != 0 {
+            unsafe { xcb_disconnect(cn as *mut _); }
+            return Err(Error::XcbConnection(code));
+        }
 
         // Get the information for the screen that initiated the connection
         let screen = unsafe {
@@ -92,13 +92,13 @@ impl NativeWindow {
             screen_iter.data
         };
 
-        Some((cn, screen))
+        Ok((cn, screen))
     }
 
     fn setup_window(
         cn: *const xcb_connection_t,
         screen: *const xcb_screen_t,
-        title: &str,
+        title: &CStr,
         width: u16,
         height: u16
     ) -> xcb_window_t {
@@ -129,7 +129,7 @@ impl NativeWindow {
                 XCB_ATOM_WM_NAME,
                 XCB_ATOM_STRING,
                 8,
-                title.as_bytes().len() as u32,
+                title.to_bytes().len() as u32,
                 title.as_ptr() as *const _
             );
 
@@ -141,7 +141,7 @@ impl NativeWindow {
     fn setup_atoms(
         cn: *const xcb_connection_t,
         wn: xcb_window_t
-    ) -> Option<xcb_atom_t> {
+    ) -> Result<xcb_atom_t> {
         let cn = cn as *mut _;
 
         // We want to watch for the delete window event
@@ -163,10 +163,13 @@ impl NativeWindow {
 
             let protocol = xcb_intern_atom_reply(cn, p_ck, null_mut());
             let delete = xcb_intern_atom_reply(cn, d_ck, null_mut());
-            if protocol == null_mut() { return None; }
+            if protocol == null_mut() {
+                if delete != null_mut() { free(delete as *mut _); }
+                return Err(Error::XcbAtom("WM_PROTOCOLS"));
+            }
             if delete == null_mut() {
-                dealloc(protocol);
-                return None;
+                free(protocol as *mut _);
+                return Err(Error::XcbAtom("WM_DELETE_WINDOW"));
             }
             xcb_change_property(
                 cn,
@@ -179,10 +182,12 @@ impl NativeWindow {
                 &mut (*delete).atom as *mut u32 as *mut _
             );
             let delete_atom = (*delete).atom;
-            dealloc(protocol);
-            dealloc(delete);
 
-            Some(delete_atom)
+            // Replies and events are malloc()ed by libxcb, not lstd
+            free(protocol as *mut _);
+            free(delete as *mut _);
+
+            Ok(delete_atom)
         }
     }
 }
@@ -224,14 +229,36 @@ impl Window for NativeWindow {
                     }
                 },
 
+                // Errors from earlier requests arrive as events
+                0 => {
+                    type T = *mut xcb_generic_event_t;
+                    type U = *mut xcb_generic_error_t;
+                    let e = unsafe { transmute::<T, U>(event) };
+                    let e = unsafe { &*e };
+
+                    lstd::error!(
+                        target: "xcb",
+                        "request {} (major {}, minor {}) failed with error {} on resource {:#x}",
+                        e.sequence,
+                        e.major_code,
+                        e.minor_code,
+                        e.error_code,
+                        e.resource_id
+                    );
+                },
+
                 _ => {}
             };
 
-            dealloc(event);
+            unsafe { free(event as *mut _); }
             event = unsafe { xcb_poll_for_event(cn) };
         }
     }
 
+    fn size(&self) -> (u16, u16) {
+        (self.width.get(), self.height.get())
+    }
+
     fn get_os_details(&self) -> (*const xcb_connection_t, xcb_window_t) {
         (self.connection, self.window)
     }
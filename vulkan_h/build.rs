@@ -82,10 +82,20 @@ fn main() {
                 "--whitelist-type", "VkDeviceQueueCreatInfo",
                 "--whitelist-type", "VkDeviceCreateInfo",
                 "--whitelist-type", "VkPhysicalDeviceFeatures",
+                "--whitelist-type", "PFN_vkCreateDebugUtilsMessengerEXT",
+                "--whitelist-type", "PFN_vkDestroyDebugUtilsMessengerEXT",
+                "--whitelist-type", "VkDebugUtilsMessengerEXT",
+                "--whitelist-type", "VkDebugUtilsMessengerCreateInfoEXT",
+                "--whitelist-type", "VkDebugUtilsMessengerCallbackDataEXT",
+                "--whitelist-type", "VkDebugUtilsMessageSeverityFlagBitsEXT",
+                "--whitelist-type", "VkDebugUtilsMessageTypeFlagBitsEXT",
                 "--whitelist-var", "VK_SUCCESS",
+                "--whitelist-var", "VK_ERROR_.*",
                 "--whitelist-var", "VK_KHR_SURFACE_EXTENSION_NAME",
                 "--whitelist-var", "VK_KHR_XCB_SURFACE_EXTENSION_NAME",
                 "--whitelist-var", "VK_KHR_SWAPCHAIN_EXTENSION_NAME",
+                "--whitelist-var", "VK_EXT_DEBUG_UTILS_EXTENSION_NAME",
+                "--whitelist-var", "VK_FALSE",
                 "--whitelist-var", "VK_MAX_PHYSICAL_DEVICE_NAME_SIZE",
                 "--",
                 "-DVK_USE_PLATFORM_XCB_KHR"
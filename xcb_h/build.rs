@@ -26,6 +26,7 @@ fn main() {
                 "--use-core",
                 "--ctypes-prefix", "c::types",
                 "--whitelist-function", "xcb_connect",
+                "--whitelist-function", "xcb_connection_has_error",
                 "--whitelist-function", "xcb_get_setup",
                 "--whitelist-function", "xcb_setup_roots_iterator",
                 "--whitelist-function", "xcb_screen_next",
@@ -51,6 +52,7 @@ fn main() {
                 "--whitelist-type", "xcb_atom_enum_t",
                 "--whitelist-type", "xcb_client_message_event_t",
                 "--whitelist-type", "xcb_configure_notify_event_t",
+                "--whitelist-type", "xcb_generic_error_t",
                 "--whitelist-var", "XCB_COPY_FROM_PARENT",
                 "--whitelist-var", "XCB_CONFIGURE_NOTIFY",
                 "--whitelist-var", "XCB_CLIENT_MESSAGE"